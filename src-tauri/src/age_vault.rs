@@ -0,0 +1,202 @@
+use crate::error::{AppError, AppResult};
+use age::secrecy::Secret;
+use log::{error, info, warn};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Шифрование "в покое" (at-rest) для ключевого материала, который крейт все же
+/// вынужден класть на диск - сконвертированные PPK-ключи (см. `ssh::AuthMethod::PuttyKey`)
+/// и хранилище управляемых ключей (см. `keys::import_ssh_key`/`create_ssh_key`). По
+/// умолчанию данные шифруются для X25519-идентити, сохраняемой в app_data_dir между
+/// сессиями (аналогично `security::init_encryption`); `set_passphrase_recipient`
+/// переключает на passphrase-based scrypt-получателя вместо нее.
+static IDENTITY: Mutex<Option<age::x25519::Identity>> = Mutex::new(None);
+static IDENTITY_FILE_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+static PASSPHRASE_RECIPIENT: Mutex<Option<String>> = Mutex::new(None);
+
+/// Инициализирует at-rest шифрование, загружая или генерируя X25519-идентити.
+/// Вызывается один раз из `main.rs::setup`, аналогично `security::init_encryption`.
+pub fn init_age_vault(app_data_dir: Option<PathBuf>) {
+    let identity = if let Some(app_dir) = app_data_dir {
+        if let Ok(mut guard) = IDENTITY_FILE_PATH.lock() {
+            *guard = Some(app_dir.join("age_identity.txt"));
+        }
+
+        load_identity().unwrap_or_else(|| {
+            info!("Generating new at-rest encryption identity (age/X25519)");
+            let new_identity = age::x25519::Identity::generate();
+            if let Err(e) = save_identity(&new_identity) {
+                error!("Failed to save at-rest encryption identity: {}", e);
+            }
+            new_identity
+        })
+    } else {
+        warn!("No app_data_dir provided, using temporary at-rest encryption identity (won't persist between sessions)");
+        age::x25519::Identity::generate()
+    };
+
+    let mut guard = IDENTITY.lock().unwrap_or_else(|e| {
+        error!("Failed to lock age identity mutex: {}", e);
+        e.into_inner()
+    });
+    *guard = Some(identity);
+}
+
+/// Загружает ранее сохраненную X25519-идентити из файла
+fn load_identity() -> Option<age::x25519::Identity> {
+    let path = IDENTITY_FILE_PATH.lock().ok()?.as_ref()?.clone();
+    let content = fs::read_to_string(&path).ok()?;
+    let identity: age::x25519::Identity = content.trim().parse().ok()?;
+    info!("At-rest encryption identity loaded from file");
+    Some(identity)
+}
+
+/// Сохраняет X25519-идентити в файл с правами только для владельца (unix)
+fn save_identity(identity: &age::x25519::Identity) -> Result<(), String> {
+    let path = IDENTITY_FILE_PATH
+        .lock()
+        .map_err(|e| format!("Failed to lock identity file path mutex: {}", e))?
+        .as_ref()
+        .ok_or("Identity file path not set")?
+        .clone();
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create at-rest encryption identity directory: {}", e))?;
+    }
+
+    fs::write(&path, identity.to_string())
+        .map_err(|e| format!("Failed to write at-rest encryption identity to file: {}", e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(&path)
+            .map_err(|e| format!("Failed to get file metadata: {}", e))?
+            .permissions();
+        perms.set_mode(0o600); // rw------- только для владельца
+        fs::set_permissions(&path, perms)
+            .map_err(|e| format!("Failed to set file permissions: {}", e))?;
+    }
+
+    info!("At-rest encryption identity saved to file");
+    Ok(())
+}
+
+/// Конфигурационный переключатель: задает passphrase-based scrypt-получателя вместо
+/// X25519-идентити для всего последующего at-rest шифрования. `None` возвращает
+/// шифрование к X25519-идентити по умолчанию.
+pub fn set_passphrase_recipient(passphrase: Option<String>) {
+    let mut guard = PASSPHRASE_RECIPIENT.lock().unwrap_or_else(|e| e.into_inner());
+    *guard = passphrase;
+}
+
+/// Шифрует `plaintext` в формате age для хранения на диске - получателем выступает
+/// либо текущий passphrase-получатель (если задан через `set_passphrase_recipient`),
+/// либо X25519-идентити, загруженная/сгенерированная в `init_age_vault`.
+pub fn encrypt_at_rest(plaintext: &[u8]) -> AppResult<Vec<u8>> {
+    let passphrase = PASSPHRASE_RECIPIENT.lock().ok().and_then(|guard| guard.clone());
+
+    let encryptor = if let Some(passphrase) = passphrase {
+        age::Encryptor::with_user_passphrase(Secret::new(passphrase))
+    } else {
+        let identity_guard = IDENTITY
+            .lock()
+            .map_err(|_| AppError::SecurityError("Failed to lock at-rest encryption identity mutex".to_string()))?;
+        let identity = identity_guard
+            .as_ref()
+            .ok_or_else(|| AppError::SecurityError("At-rest encryption not initialized".to_string()))?;
+        age::Encryptor::with_recipients(vec![Box::new(identity.to_public())])
+            .ok_or_else(|| AppError::SecurityError("Failed to create age encryptor".to_string()))?
+    };
+
+    let mut output = Vec::new();
+    let mut writer = encryptor
+        .wrap_output(&mut output)
+        .map_err(|e| AppError::SecurityError(format!("Failed to start at-rest encryption: {}", e)))?;
+    writer
+        .write_all(plaintext)
+        .map_err(|e| AppError::SecurityError(format!("Failed to encrypt key material: {}", e)))?;
+    writer
+        .finish()
+        .map_err(|e| AppError::SecurityError(format!("Failed to finalize at-rest encryption: {}", e)))?;
+
+    Ok(output)
+}
+
+/// Расшифровывает данные, зашифрованные `encrypt_at_rest`
+pub fn decrypt_at_rest(ciphertext: &[u8]) -> AppResult<Vec<u8>> {
+    let passphrase = PASSPHRASE_RECIPIENT.lock().ok().and_then(|guard| guard.clone());
+
+    let decryptor = age::Decryptor::new(ciphertext)
+        .map_err(|e| AppError::SecurityError(format!("Failed to read at-rest encryption envelope: {}", e)))?;
+
+    let mut reader = match (&decryptor, passphrase) {
+        (age::Decryptor::Passphrase(d), Some(passphrase)) => d
+            .decrypt(&Secret::new(passphrase), None)
+            .map_err(|e| AppError::SecurityError(format!("Failed to decrypt key material: {}", e)))?,
+        (age::Decryptor::Recipients(d), _) => {
+            let identity_guard = IDENTITY
+                .lock()
+                .map_err(|_| AppError::SecurityError("Failed to lock at-rest encryption identity mutex".to_string()))?;
+            let identity = identity_guard
+                .as_ref()
+                .ok_or_else(|| AppError::SecurityError("At-rest encryption not initialized".to_string()))?;
+            d.decrypt(std::iter::once(identity as &dyn age::Identity))
+                .map_err(|e| AppError::SecurityError(format!("Failed to decrypt key material: {}", e)))?
+        }
+        _ => {
+            return Err(AppError::SecurityError(
+                "Key material was encrypted with a different recipient type than currently configured".to_string(),
+            ))
+        }
+    };
+
+    let mut plaintext = Vec::new();
+    reader
+        .read_to_end(&mut plaintext)
+        .map_err(|e| AppError::SecurityError(format!("Failed to read decrypted key material: {}", e)))?;
+    Ok(plaintext)
+}
+
+/// RAII-обертка для временного файла с расшифрованным ключевым материалом (нужен,
+/// потому что ssh2/libssh2 и `ssh-keygen` принимают только путь к файлу на диске).
+/// Гарантирует удаление файла при выходе из области видимости, в том числе при раннем
+/// возврате по ошибке или при панике - в отличие от точечного `std::fs::remove_file`
+/// после одного конкретного места использования.
+pub struct TempKeyFile {
+    path: PathBuf,
+}
+
+impl TempKeyFile {
+    /// Записывает `contents` во временный файл с правами только для владельца (unix)
+    pub fn write(contents: &[u8]) -> AppResult<Self> {
+        let path = std::env::temp_dir().join(format!("ssh_key_{}.tmp", Uuid::new_v4()));
+        fs::write(&path, contents).map_err(|e| AppError::SshError(format!("Failed to write temp key file: {}", e)))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if let Ok(metadata) = fs::metadata(&path) {
+                let mut perms = metadata.permissions();
+                perms.set_mode(0o600);
+                let _ = fs::set_permissions(&path, perms);
+            }
+        }
+
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempKeyFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
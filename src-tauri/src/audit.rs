@@ -1,12 +1,20 @@
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::PathBuf;
 use std::sync::Mutex;
-use std::sync::atomic::{AtomicU64, Ordering};
-use tauri::AppHandle;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use tauri::{AppHandle, Manager};
 use chrono::{Utc, DateTime, TimeDelta};
 use std::fs;
+use std::sync::OnceLock;
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use tracing::{Level, Subscriber};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, reload, EnvFilter, Layer};
+use tracing_subscriber::prelude::*;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuditLog {
@@ -20,6 +28,58 @@ pub struct AuditLog {
 static AUDIT_FILE: Mutex<Option<PathBuf>> = Mutex::new(None);
 static AUDIT_SETTINGS: Mutex<Option<AuditSettings>> = Mutex::new(None);
 
+// AppHandle, сохраняемый при инициализации, чтобы log_action мог эмитить события в GUI
+// без необходимости протаскивать Window через каждый вызов-сайт по всему коду
+static AUDIT_APP_HANDLE: Mutex<Option<AppHandle>> = Mutex::new(None);
+
+// Кольцевой буфер последних записей аудита в памяти - позволяет только что открытому
+// окну запросить "повтор" последних N записей командой `subscribe_audit_stream`,
+// не дожидаясь чтения всего файла журнала
+static AUDIT_RING: Mutex<VecDeque<AuditLog>> = Mutex::new(VecDeque::new());
+const AUDIT_RING_CAPACITY: usize = 200;
+
+/// Имя Tauri-события, в которое транслируется каждая новая запись аудита в реальном времени
+const AUDIT_STREAM_EVENT: &str = "audit://entry";
+
+// Соединение со SQLite базой для индексированного, запрашиваемого хранилища аудита.
+// Файловый журнал (AUDIT_FILE) сохраняется как есть для ручного просмотра/grep,
+// SQLite используется как основной источник для query_audit_logs/пагинации.
+static AUDIT_DB: Mutex<Option<rusqlite::Connection>> = Mutex::new(None);
+
+// ID последней строки, экспортированной в TimescaleDB/Postgres - позволяет
+// экспортировать только новые записи при повторных вызовах
+static LAST_EXPORTED_ID: AtomicI64 = AtomicI64::new(0);
+
+// Идентификатор текущего запуска приложения - используется как колонка session id
+// в удаленном приемнике аудита, чтобы строки из разных процессов/машин не смешивались
+fn session_id() -> &'static str {
+    static ID: OnceLock<String> = OnceLock::new();
+    ID.get_or_init(|| uuid::Uuid::new_v4().to_string())
+}
+
+// Отправляющий конец канала к фоновому воркеру удаленного приемника аудита, плюс флаг
+// его остановки - заменяются целиком при каждой переконфигурации через `update_audit_settings`
+struct RemoteSinkHandle {
+    tx: mpsc::SyncSender<AuditLog>,
+    stop_flag: std::sync::Arc<AtomicBool>,
+}
+
+static REMOTE_SINK_HANDLE: Mutex<Option<RemoteSinkHandle>> = Mutex::new(None);
+
+const REMOTE_SINK_CHANNEL_CAPACITY: usize = 10_000;
+const REMOTE_SINK_MAX_BACKOFF_SECS: u64 = 60;
+
+// Ручка для переконфигурации уровня логирования `EnvFilter` в рантайме (при изменении
+// `log_level` через `update_audit_settings`), без пересоздания всего subscriber'а
+static RELOAD_HANDLE: OnceLock<reload::Handle<EnvFilter, tracing_subscriber::Registry>> = OnceLock::new();
+
+fn host_extract_regex() -> &'static regex::Regex {
+    static RE: OnceLock<regex::Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        regex::Regex::new(r"\b(?:\d{1,3}\.){3}\d{1,3}\b").expect("host extraction regex is invalid")
+    })
+}
+
 // Счётчик для периодической очистки логов (вместо каждого вызова)
 static LOG_ACTION_COUNTER: AtomicU64 = AtomicU64::new(0);
 // Последняя очистка логов (timestamp в секундах)
@@ -27,6 +87,17 @@ static LAST_CLEANUP_TIME: AtomicU64 = AtomicU64::new(0);
 // Интервал между очистками (1 час = 3600 секунд)
 const CLEANUP_INTERVAL_SECS: u64 = 3600;
 
+/// Конфигурация удаленного приемника аудита (PostgreSQL/TimescaleDB) - в отличие от
+/// разового `export_audit_to_timeseries`, при заданном `remote_sink` каждая новая
+/// запись аудита потоково отправляется фоновым воркером почти в реальном времени
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RemoteSinkConfig {
+    pub connection_string: String,
+    pub table: String,
+    pub batch_size: usize,
+    pub flush_interval_secs: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuditSettings {
     pub log_level: String, // "error" | "warn" | "info" | "debug"
@@ -35,6 +106,23 @@ pub struct AuditSettings {
     pub max_log_file_size: u64, // в МБ
     pub log_format: String, // "json" | "text"
     pub enable_audit: bool,
+    #[serde(default)]
+    pub remote_sink: Option<RemoteSinkConfig>,
+    /// Максимальное количество хранимых gzip-архивов ротации (0 = без ограничения)
+    #[serde(default = "default_max_archives")]
+    pub max_archives: u32,
+    /// Суммарный лимит размера всех архивов в МБ, сверх которого самые старые удаляются
+    /// (0 = без ограничения)
+    #[serde(default = "default_max_archive_total_size_mb")]
+    pub max_archive_total_size_mb: u64,
+}
+
+fn default_max_archives() -> u32 {
+    20
+}
+
+fn default_max_archive_total_size_mb() -> u64 {
+    500
 }
 
 impl Default for AuditSettings {
@@ -46,17 +134,146 @@ impl Default for AuditSettings {
             max_log_file_size: 100,
             log_format: "json".to_string(),
             enable_audit: true,
+            remote_sink: None,
+            max_archives: default_max_archives(),
+            max_archive_total_size_mb: default_max_archive_total_size_mb(),
         }
     }
 }
 
-// Обновляет настройки аудита
+// Обновляет настройки аудита. Если `remote_sink` изменился (добавлен, удален или
+// переконфигурирован), соответствующим образом останавливает/перезапускает фоновый
+// воркер потоковой отправки в удаленную БД
 pub fn update_audit_settings(settings: AuditSettings) {
+    let previous_sink = AUDIT_SETTINGS.lock().ok().and_then(|g| g.as_ref().and_then(|s| s.remote_sink.clone()));
+    if previous_sink != settings.remote_sink {
+        reconfigure_remote_sink(settings.remote_sink.clone());
+    }
+
+    reconfigure_tracing_filter(&settings.log_level);
+
     if let Ok(mut guard) = AUDIT_SETTINGS.lock() {
         *guard = Some(settings);
     }
 }
 
+// Перестраивает `EnvFilter` глобального tracing subscriber'а под новый минимальный уровень,
+// не пересоздавая сам subscriber (см. `init_tracing`)
+fn reconfigure_tracing_filter(log_level: &str) {
+    if let Some(handle) = RELOAD_HANDLE.get() {
+        match EnvFilter::try_new(log_level) {
+            Ok(filter) => {
+                if let Err(e) = handle.reload(filter) {
+                    log::warn!("Failed to reload tracing filter: {}", e);
+                }
+            }
+            Err(e) => log::warn!("Invalid log level '{}' for tracing filter: {}", log_level, e),
+        }
+    }
+}
+
+/// Устанавливает глобальный tracing subscriber, через который проходят и записи аудита,
+/// и обычные `log::` вызовы по всему коду (через мост `tracing_log::LogTracer`). Должна
+/// вызываться один раз, как можно раньше в `main()` - до этого момента `AUDIT_FILE`/
+/// `AUDIT_APP_HANDLE` еще не инициализированы, но `AuditLayer` читает их лениво при
+/// каждом событии, поэтому порядок не важен.
+pub fn init_tracing() {
+    if tracing_log::LogTracer::init().is_err() {
+        eprintln!("tracing_log::LogTracer уже инициализирован");
+    }
+
+    let default_filter = EnvFilter::try_new(&AuditSettings::default().log_level)
+        .unwrap_or_else(|_| EnvFilter::new("info"));
+    let (filter_layer, handle) = reload::Layer::new(default_filter);
+    let _ = RELOAD_HANDLE.set(handle);
+
+    let subscriber = tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(tracing_subscriber::fmt::layer())
+        .with(AuditLayer);
+
+    if tracing::subscriber::set_global_default(subscriber).is_err() {
+        eprintln!("Глобальный tracing subscriber уже установлен");
+    }
+}
+
+/// Собирает поля `action`/`details`/`user` из события tracing (записанных либо явно
+/// через `log_action`, либо - для обычных `log::`-вызовов, пришедших через мост -
+/// неявно как `message`, откуда они используются в качестве деталей записи)
+#[derive(Default)]
+struct AuditVisitor {
+    action: Option<String>,
+    details: Option<String>,
+    user: Option<String>,
+    message: Option<String>,
+}
+
+impl tracing::field::Visit for AuditVisitor {
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        match field.name() {
+            "action" => self.action = Some(value.to_string()),
+            "details" => self.details = Some(value.to_string()),
+            "user" => self.user = Some(value.to_string()),
+            "message" => self.message = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        let formatted = format!("{:?}", value);
+        match field.name() {
+            "action" => self.action = Some(formatted),
+            "details" => self.details = Some(formatted),
+            "user" => self.user = Some(formatted),
+            "message" => self.message = Some(formatted),
+            _ => {}
+        }
+    }
+}
+
+/// Слой tracing, превращающий каждое прошедшее фильтр событие в `AuditLog` и
+/// прогоняющий его через тот же конвейер хранения/трансляции, что и раньше
+/// (файл, SQLite, кольцевой буфер + GUI-событие, удаленный приемник)
+struct AuditLayer;
+
+impl<S> Layer<S> for AuditLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+        let settings = get_audit_settings();
+        if !settings.enable_audit {
+            return;
+        }
+
+        let meta = event.metadata();
+        let level = match *meta.level() {
+            Level::ERROR => "ERROR",
+            Level::WARN => "WARN",
+            Level::INFO => "INFO",
+            Level::DEBUG | Level::TRACE => "DEBUG",
+        };
+
+        let mut visitor = AuditVisitor::default();
+        event.record(&mut visitor);
+
+        let action = visitor.action.unwrap_or_else(|| meta.target().to_string());
+        let details = visitor.details.or(visitor.message).unwrap_or_default();
+
+        LOG_ACTION_COUNTER.fetch_add(1, Ordering::Relaxed);
+
+        let entry = AuditLog {
+            timestamp: Utc::now().to_rfc3339(),
+            level: level.to_string(),
+            action,
+            details,
+            user: visitor.user,
+        };
+
+        dispatch_audit_log(entry, &settings);
+    }
+}
+
 pub fn init_audit_log(app: AppHandle) {
     let app_data_dir = match app.path_resolver().app_data_dir() {
         Some(dir) => dir,
@@ -78,10 +295,55 @@ pub fn init_audit_log(app: AppHandle) {
         log::error!("Failed to lock audit file mutex");
         return;
     }
-    
+
+    if let Ok(mut guard) = AUDIT_APP_HANDLE.lock() {
+        *guard = Some(app);
+    }
+
+    init_audit_db(&app_data_dir.join("audit.db"));
+
+    let settings = get_audit_settings();
+    reconfigure_remote_sink(settings.remote_sink.clone());
+    reconfigure_tracing_filter(&settings.log_level);
+
     log_action("INFO", "Система", "Приложение запущено", None);
 }
 
+// Инициализирует SQLite базу журнала аудита с индексами по времени, уровню, действию и хосту
+fn init_audit_db(db_path: &PathBuf) {
+    match rusqlite::Connection::open(db_path) {
+        Ok(conn) => {
+            let schema_result = conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS audit_log (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    timestamp TEXT NOT NULL,
+                    level TEXT NOT NULL,
+                    action TEXT NOT NULL,
+                    host TEXT,
+                    message TEXT NOT NULL,
+                    user TEXT
+                );
+                CREATE INDEX IF NOT EXISTS idx_audit_timestamp ON audit_log(timestamp);
+                CREATE INDEX IF NOT EXISTS idx_audit_level ON audit_log(level);
+                CREATE INDEX IF NOT EXISTS idx_audit_action ON audit_log(action);
+                CREATE INDEX IF NOT EXISTS idx_audit_host ON audit_log(host);",
+            );
+
+            if let Err(e) = schema_result {
+                log::error!("Failed to create audit_log schema: {}", e);
+                return;
+            }
+
+            if let Ok(mut guard) = AUDIT_DB.lock() {
+                *guard = Some(conn);
+            } else {
+                log::error!("Failed to lock audit DB mutex");
+            }
+        }
+        Err(e) => log::error!("Failed to open audit SQLite database at {:?}: {}", db_path, e),
+    }
+}
+
 // Получает настройки аудита
 fn get_audit_settings() -> AuditSettings {
     if let Ok(guard) = AUDIT_SETTINGS.lock() {
@@ -93,76 +355,127 @@ fn get_audit_settings() -> AuditSettings {
     AuditSettings::default()
 }
 
-// Проверяет, должен ли уровень логирования быть записан
-fn should_log_level(level: &str, min_level: &str) -> bool {
-    let levels = vec!["debug", "info", "warn", "error"];
-    let level_idx = levels.iter().position(|&l| l == level.to_lowercase()).unwrap_or(0);
-    let min_level_idx = levels.iter().position(|&l| l == min_level.to_lowercase()).unwrap_or(0);
-    level_idx >= min_level_idx
-}
-
-// Проверяет размер файла и выполняет ротацию если нужно
-fn check_and_rotate_log(log_path: &PathBuf, max_size_mb: u64) -> Result<(), String> {
+// Проверяет размер файла и выполняет ротацию если нужно: в отличие от прежнего поведения
+// (усечение до последних 100 строк), текущий файл целиком перемещается в gzip-архив с
+// timestamp в имени, а `audit.log` начинается заново пустым - история не теряется
+fn check_and_rotate_log(log_path: &PathBuf, max_size_mb: u64, max_archives: u32, max_archive_total_size_mb: u64) -> Result<(), String> {
     if let Ok(metadata) = fs::metadata(log_path) {
         let size_mb = metadata.len() / (1024 * 1024);
         if size_mb >= max_size_mb {
-            // Создаем резервную копию с timestamp
-            let backup_path = log_path.with_extension(format!("log.{}", Utc::now().timestamp()));
-            if let Err(e) = fs::copy(log_path, &backup_path) {
-                return Err(format!("Failed to create backup: {}", e));
-            }
-            // Очищаем основной файл, оставляя только последние 100 строк
-            if let Ok(content) = fs::read_to_string(log_path) {
-                let lines: Vec<&str> = content.lines().collect();
-                let keep_lines = if lines.len() > 100 { 100 } else { lines.len() };
-                let new_content = lines[lines.len() - keep_lines..].join("\n");
-                if let Err(e) = fs::write(log_path, new_content) {
-                    return Err(format!("Failed to rotate log: {}", e));
-                }
-            }
+            archive_and_truncate_log(log_path)?;
+            enforce_archive_caps(log_path, max_archives, max_archive_total_size_mb);
         }
     }
     Ok(())
 }
 
-// Очищает старые логи по retentionDays
-fn cleanup_old_logs(log_path: &PathBuf, retention_days: u32) -> Result<(), String> {
-    if retention_days == 0 {
-        return Ok(());
-    }
-    
-    let cutoff_date = Utc::now() - TimeDelta::try_days(retention_days as i64).unwrap_or(TimeDelta::zero());
-    
-    if let Ok(content) = fs::read_to_string(log_path) {
-        let lines: Vec<&str> = content.lines().collect();
-        let total_lines = lines.len();
-        let mut kept_lines = Vec::new();
-        
-        for line in &lines {
-            if let Ok(log_entry) = serde_json::from_str::<AuditLog>(line) {
-                if let Ok(timestamp) = DateTime::parse_from_rfc3339(&log_entry.timestamp) {
-                    let log_date = timestamp.with_timezone(&Utc);
-                    if log_date >= cutoff_date {
-                        kept_lines.push(*line);
+// Архивирует текущий `audit.log` в `audit.log.<unix_ts>.gz` и очищает живой файл
+fn archive_and_truncate_log(log_path: &PathBuf) -> Result<(), String> {
+    let content = fs::read(log_path).map_err(|e| format!("Failed to read log for rotation: {}", e))?;
+
+    let archive_path = log_path.with_extension(format!("log.{}.gz", Utc::now().timestamp()));
+    let archive_file = fs::File::create(&archive_path)
+        .map_err(|e| format!("Failed to create log archive: {}", e))?;
+    let mut encoder = flate2::write::GzEncoder::new(archive_file, flate2::Compression::default());
+    encoder
+        .write_all(&content)
+        .map_err(|e| format!("Failed to write log archive: {}", e))?;
+    encoder
+        .finish()
+        .map_err(|e| format!("Failed to finalize log archive: {}", e))?;
+
+    fs::write(log_path, b"").map_err(|e| format!("Failed to truncate live log: {}", e))?;
+
+    Ok(())
+}
+
+// Возвращает список gzip-архивов ротации рядом с `log_path`, отсортированный от
+// самых новых к самым старым по timestamp, зашитому в имя файла
+fn list_log_archives(log_path: &PathBuf) -> Vec<(i64, PathBuf)> {
+    let dir = match log_path.parent() {
+        Some(d) => d,
+        None => return Vec::new(),
+    };
+    let prefix = format!(
+        "{}.",
+        log_path.file_name().and_then(|n| n.to_str()).unwrap_or("audit.log")
+    );
+
+    let mut archives = Vec::new();
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                if let Some(rest) = name.strip_prefix(&prefix) {
+                    if let Some(ts_str) = rest.strip_suffix(".gz") {
+                        if let Ok(ts) = ts_str.parse::<i64>() {
+                            archives.push((ts, path));
+                        }
                     }
-                } else {
-                    // Если не удалось распарсить дату, оставляем строку
-                    kept_lines.push(*line);
                 }
-            } else {
-                // Если не JSON, оставляем строку
-                kept_lines.push(*line);
             }
         }
-        
-        if kept_lines.len() < total_lines {
-            let new_content = kept_lines.join("\n");
-            if let Err(e) = fs::write(log_path, new_content) {
-                return Err(format!("Failed to cleanup old logs: {}", e));
+    }
+    archives.sort_by(|a, b| b.0.cmp(&a.0));
+    archives
+}
+
+// Удаляет самые старые архивы сверх `max_archives` штук и/или сверх суммарного размера
+// `max_archive_total_size_mb` (0 у любого из параметров означает отсутствие лимита)
+fn enforce_archive_caps(log_path: &PathBuf, max_archives: u32, max_archive_total_size_mb: u64) {
+    let mut keep = list_log_archives(log_path);
+
+    if max_archives > 0 && keep.len() as u32 > max_archives {
+        keep.truncate(max_archives as usize);
+    }
+
+    if max_archive_total_size_mb > 0 {
+        let max_bytes = max_archive_total_size_mb * 1024 * 1024;
+        let mut total: u64 = 0;
+        let mut cut_at = keep.len();
+        for (i, (_, path)) in keep.iter().enumerate() {
+            total += fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+            if total > max_bytes {
+                cut_at = i;
+                break;
             }
         }
+        keep.truncate(cut_at);
     }
-    
+
+    let keep_paths: std::collections::HashSet<&PathBuf> = keep.iter().map(|(_, p)| p).collect();
+    for (_, path) in list_log_archives(log_path) {
+        if !keep_paths.contains(&path) {
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
+
+// Читает и разжимает один gzip-архив ротации, разбирая его построчно как JSON (тот же
+// формат, что и живой `audit.log` при `log_format = "json"`)
+fn read_archived_logs(archive_path: &PathBuf) -> Option<Vec<AuditLog>> {
+    let file = fs::File::open(archive_path).ok()?;
+    let mut decoder = flate2::read::GzDecoder::new(file);
+    let mut content = String::new();
+    std::io::Read::read_to_string(&mut decoder, &mut content).ok()?;
+    Some(content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+}
+
+// Удаляет архивы ротации старше retentionDays - сам живой файл больше не
+// парсится/перезаписывается построчно на каждый проход обслуживания
+fn cleanup_old_logs(log_path: &PathBuf, retention_days: u32) -> Result<(), String> {
+    if retention_days == 0 {
+        return Ok(());
+    }
+
+    let cutoff_ts = (Utc::now() - TimeDelta::try_days(retention_days as i64).unwrap_or(TimeDelta::zero())).timestamp();
+
+    for (ts, path) in list_log_archives(log_path) {
+        if ts < cutoff_ts {
+            let _ = fs::remove_file(&path);
+        }
+    }
+
     Ok(())
 }
 
@@ -186,43 +499,43 @@ fn should_run_maintenance() -> bool {
     }
 }
 
+/// Тонкая обертка над tracing: раньше здесь была вся логика записи/трансляции записи
+/// аудита, теперь она переехала в `AuditLayer::on_event` + `dispatch_audit_log`, а
+/// `log_action` лишь публикует событие с полями `action`/`details`/`user` на нужном
+/// уровне - фильтрация по `enable_audit`/`log_level` применяется слоем/`EnvFilter`
 pub fn log_action(level: &str, action: &str, details: &str, user: Option<&str>) {
-    let settings = get_audit_settings();
-    
-    // Проверяем, включен ли аудит
-    if !settings.enable_audit {
-        return;
+    match (level.to_lowercase().as_str(), user) {
+        ("error", Some(u)) => tracing::error!(action, details, user = u),
+        ("error", None) => tracing::error!(action, details),
+        ("warn", Some(u)) => tracing::warn!(action, details, user = u),
+        ("warn", None) => tracing::warn!(action, details),
+        ("debug", Some(u)) => tracing::debug!(action, details, user = u),
+        ("debug", None) => tracing::debug!(action, details),
+        (_, Some(u)) => tracing::info!(action, details, user = u),
+        (_, None) => tracing::info!(action, details),
     }
-    
-    // Проверяем уровень логирования
-    if !should_log_level(level, &settings.log_level) {
-        return;
-    }
-    
-    // Увеличиваем счётчик вызовов
-    LOG_ACTION_COUNTER.fetch_add(1, Ordering::Relaxed);
-    
-    let log_entry = AuditLog {
-        timestamp: Utc::now().to_rfc3339(),
-        level: level.to_string(),
-        action: action.to_string(),
-        details: details.to_string(),
-        user: user.map(|s| s.to_string()),
-    };
+}
 
+/// Прогоняет уже собранную запись аудита через конвейер хранения/трансляции: файл
+/// (с периодическим обслуживанием - ротацией и очисткой), SQLite, удаленный приемник
+/// и кольцевой буфер + GUI-событие. Вызывается из `AuditLayer::on_event` для каждого
+/// события, прошедшего фильтр уровня и проверку `enable_audit`.
+fn dispatch_audit_log(entry: AuditLog, settings: &AuditSettings) {
     if let Ok(guard) = AUDIT_FILE.lock() {
         if let Some(ref log_path) = *guard {
             // Периодическое обслуживание: ротация и очистка выполняются раз в час
             if should_run_maintenance() {
                 // Проверяем размер и ротируем если нужно
                 if settings.auto_rotate {
-                    let _ = check_and_rotate_log(log_path, settings.max_log_file_size);
+                    let _ = check_and_rotate_log(log_path, settings.max_log_file_size, settings.max_archives, settings.max_archive_total_size_mb);
+                    rotate_audit_db_if_needed(settings.max_log_file_size);
                 }
-                
+
                 // Очищаем старые логи
                 let _ = cleanup_old_logs(log_path, settings.retention_days);
+                cleanup_old_logs_db(settings.retention_days);
             }
-            
+
             if let Ok(mut file) = OpenOptions::new()
                 .create(true)
                 .append(true)
@@ -232,50 +545,494 @@ pub fn log_action(level: &str, action: &str, details: &str, user: Option<&str>)
                     // Текстовый формат
                     let text_line = format!(
                         "[{}] {}: {} - {}",
-                        log_entry.timestamp,
-                        log_entry.level,
-                        log_entry.action,
-                        log_entry.details
+                        entry.timestamp,
+                        entry.level,
+                        entry.action,
+                        entry.details
                     );
-                    if let Some(user) = &log_entry.user {
+                    if let Some(user) = &entry.user {
                         let _ = writeln!(file, "{} (User: {})", text_line, user);
                     } else {
                         let _ = writeln!(file, "{}", text_line);
                     }
                 } else {
                     // JSON формат (по умолчанию)
-                    if let Ok(json) = serde_json::to_string(&log_entry) {
+                    if let Ok(json) = serde_json::to_string(&entry) {
                         let _ = writeln!(file, "{}", json);
                     }
                 }
             }
         }
     }
+
+    insert_audit_log_db(&entry);
+
+    // Неблокирующая отправка воркеру удаленного приемника - если канал заполнен или
+    // воркер не запущен, просто пропускаем, не замедляя обработку события
+    try_send_to_remote_sink(entry.clone());
+
+    // Эмиссия в GUI выполняется после записи в файл и БД, когда все блокировки уже
+    // отпущены, чтобы не удерживать AUDIT_FILE/AUDIT_DB во время вызова emit
+    push_to_ring_and_emit(entry);
+}
+
+/// Добавляет запись в кольцевой буфер (вытесняя самую старую при переполнении) и
+/// транслирует ее в GUI как событие `audit://entry`
+fn push_to_ring_and_emit(entry: AuditLog) {
+    if let Ok(mut ring) = AUDIT_RING.lock() {
+        if ring.len() >= AUDIT_RING_CAPACITY {
+            ring.pop_front();
+        }
+        ring.push_back(entry.clone());
+    }
+
+    if let Ok(guard) = AUDIT_APP_HANDLE.lock() {
+        if let Some(ref app) = *guard {
+            if let Err(e) = app.emit_all(AUDIT_STREAM_EVENT, &entry) {
+                log::warn!("Failed to emit audit stream event: {}", e);
+            }
+        }
+    }
+}
+
+/// Возвращает последние (до `limit`) записи из кольцевого буфера для "повтора"
+/// недавней истории во только что открытом окне, от новых к старым
+pub fn replay_audit_stream(limit: Option<usize>) -> Vec<AuditLog> {
+    let ring = match AUDIT_RING.lock() {
+        Ok(r) => r,
+        Err(_) => return Vec::new(),
+    };
+    let mut entries: Vec<AuditLog> = ring.iter().cloned().collect();
+    entries.reverse();
+    if let Some(lim) = limit {
+        entries.truncate(lim);
+    }
+    entries
+}
+
+// Пытается извлечь хост (IPv4-адрес) из текста сообщения для индексированного поиска по хосту
+fn extract_host(details: &str) -> Option<String> {
+    host_extract_regex().find(details).map(|m| m.as_str().to_string())
+}
+
+fn insert_audit_log_db(entry: &AuditLog) {
+    let host = extract_host(&entry.details);
+    if let Ok(guard) = AUDIT_DB.lock() {
+        if let Some(ref conn) = *guard {
+            let result = conn.execute(
+                "INSERT INTO audit_log (timestamp, level, action, host, message, user) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                rusqlite::params![entry.timestamp, entry.level, entry.action, host, entry.details, entry.user],
+            );
+            if let Err(e) = result {
+                log::error!("Failed to insert audit log row into SQLite: {}", e);
+            }
+        }
+    }
 }
 
 pub fn get_audit_logs(limit: Option<usize>) -> Vec<AuditLog> {
-    if let Ok(guard) = AUDIT_FILE.lock() {
-        if let Some(ref log_path) = *guard {
-            if let Ok(content) = std::fs::read_to_string(log_path) {
-                let mut logs: Vec<AuditLog> = content
-                    .lines()
-                    .filter_map(|line| serde_json::from_str(line).ok())
-                    .collect();
-                
-                logs.reverse();
-                
-                if let Some(lim) = limit {
-                    logs.truncate(lim);
+    let log_path = match AUDIT_FILE.lock() {
+        Ok(guard) => match guard.as_ref() {
+            Some(p) => p.clone(),
+            None => return Vec::new(),
+        },
+        Err(_) => return Vec::new(),
+    };
+
+    let mut logs: Vec<AuditLog> = std::fs::read_to_string(&log_path)
+        .map(|content| content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+        .unwrap_or_default();
+    logs.reverse();
+
+    // Если живого файла не хватает на запрошенный `limit` (например, он только что
+    // ротировался), дочитываем из самых свежих gzip-архивов, пока не наберем нужное число
+    if let Some(lim) = limit {
+        if logs.len() < lim {
+            for (_, archive_path) in list_log_archives(&log_path) {
+                if logs.len() >= lim {
+                    break;
+                }
+                if let Some(mut archived) = read_archived_logs(&archive_path) {
+                    archived.reverse();
+                    logs.extend(archived);
+                }
+            }
+        }
+        logs.truncate(lim);
+    }
+
+    logs
+}
+
+/// Фильтр для запроса журнала аудита из SQLite-хранилища: диапазон времени, уровень,
+/// действие и подстрока хоста, с пагинацией через `limit`/`offset`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditLogFilter {
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub level: Option<String>,
+    pub action: Option<String>,
+    pub host: Option<String>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// Запрашивает журнал аудита из индексированного SQLite-хранилища с фильтрацией и пагинацией.
+/// Возвращает записи от новых к старым (как и файловый `get_audit_logs`)
+pub fn query_audit_logs(filter: &AuditLogFilter) -> Result<Vec<AuditLog>, String> {
+    let guard = AUDIT_DB.lock().map_err(|_| "Не удалось заблокировать мьютекс базы аудита".to_string())?;
+    let conn = guard.as_ref().ok_or_else(|| "База данных аудита не инициализирована".to_string())?;
+
+    let mut sql = String::from(
+        "SELECT timestamp, level, action, message, user FROM audit_log WHERE 1=1",
+    );
+    let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+    if let Some(start) = &filter.start_time {
+        sql.push_str(" AND timestamp >= ?");
+        params.push(Box::new(start.clone()));
+    }
+    if let Some(end) = &filter.end_time {
+        sql.push_str(" AND timestamp <= ?");
+        params.push(Box::new(end.clone()));
+    }
+    if let Some(level) = &filter.level {
+        sql.push_str(" AND level = ?");
+        params.push(Box::new(level.clone()));
+    }
+    if let Some(action) = &filter.action {
+        sql.push_str(" AND action = ?");
+        params.push(Box::new(action.clone()));
+    }
+    if let Some(host) = &filter.host {
+        sql.push_str(" AND host LIKE ?");
+        params.push(Box::new(format!("%{}%", host)));
+    }
+
+    sql.push_str(" ORDER BY id DESC");
+
+    if let Some(limit) = filter.limit {
+        sql.push_str(" LIMIT ?");
+        params.push(Box::new(limit as i64));
+        if let Some(offset) = filter.offset {
+            sql.push_str(" OFFSET ?");
+            params.push(Box::new(offset as i64));
+        }
+    }
+
+    let mut stmt = conn.prepare(&sql).map_err(|e| format!("Failed to prepare audit query: {}", e))?;
+    let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+    let rows = stmt
+        .query_map(param_refs.as_slice(), |row| {
+            Ok(AuditLog {
+                timestamp: row.get(0)?,
+                level: row.get(1)?,
+                action: row.get(2)?,
+                details: row.get(3)?,
+                user: row.get(4)?,
+            })
+        })
+        .map_err(|e| format!("Failed to execute audit query: {}", e))?;
+
+    let mut logs = Vec::new();
+    for row in rows {
+        logs.push(row.map_err(|e| format!("Failed to read audit row: {}", e))?);
+    }
+
+    Ok(logs)
+}
+
+fn cleanup_old_logs_db(retention_days: u32) {
+    if retention_days == 0 {
+        return;
+    }
+    let cutoff = (Utc::now() - TimeDelta::try_days(retention_days as i64).unwrap_or(TimeDelta::zero())).to_rfc3339();
+
+    if let Ok(guard) = AUDIT_DB.lock() {
+        if let Some(ref conn) = *guard {
+            if let Err(e) = conn.execute("DELETE FROM audit_log WHERE timestamp < ?1", rusqlite::params![cutoff]) {
+                log::error!("Failed to clean up old audit rows: {}", e);
+            }
+        }
+    }
+}
+
+// Ротация SQLite-хранилища: при превышении лимита размера удаляем самые старые строки
+// (аналог усечения файлового журнала в check_and_rotate_log)
+fn rotate_audit_db_if_needed(max_size_mb: u64) {
+    if let Ok(guard) = AUDIT_DB.lock() {
+        if let Some(ref conn) = *guard {
+            let db_path: Option<String> = conn.path().map(|p| p.to_string_lossy().to_string());
+            if let Some(path) = db_path {
+                if let Ok(metadata) = fs::metadata(&path) {
+                    let size_mb = metadata.len() / (1024 * 1024);
+                    if size_mb >= max_size_mb {
+                        // Оставляем только последние 100 000 строк, как и файловая ротация оставляет последние строки
+                        let _ = conn.execute(
+                            "DELETE FROM audit_log WHERE id NOT IN (SELECT id FROM audit_log ORDER BY id DESC LIMIT 100000)",
+                            [],
+                        );
+                        let _ = conn.execute("VACUUM", []);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Экспортирует еще не выгруженные строки журнала аудита в TimescaleDB-совместимую гипертаблицу
+/// через обычное Postgres-подключение. При первом запуске пытается создать таблицу и
+/// гипертаблицу (`create_hypertable`), игнорируя ошибку, если расширение TimescaleDB отсутствует -
+/// в этом случае запись продолжается в обычную таблицу Postgres.
+pub fn export_audit_to_timeseries(postgres_url: &str, hypertable: &str) -> Result<usize, String> {
+    let mut client = postgres::Client::connect(postgres_url, postgres::NoTls)
+        .map_err(|e| format!("Failed to connect to Postgres: {}", e))?;
+
+    let create_table_sql = format!(
+        "CREATE TABLE IF NOT EXISTS {table} (
+            id BIGINT PRIMARY KEY,
+            timestamp TIMESTAMPTZ NOT NULL,
+            level TEXT NOT NULL,
+            action TEXT NOT NULL,
+            host TEXT,
+            message TEXT NOT NULL,
+            \"user\" TEXT
+        )",
+        table = hypertable
+    );
+    client
+        .execute(create_table_sql.as_str(), &[])
+        .map_err(|e| format!("Failed to create audit timeseries table: {}", e))?;
+
+    // Превращаем обычную таблицу в гипертаблицу, если установлено расширение TimescaleDB.
+    // Если расширения нет, просто продолжаем писать в обычную таблицу Postgres.
+    let _ = client.execute(
+        format!("SELECT create_hypertable('{}', 'timestamp', if_not_exists => TRUE)", hypertable).as_str(),
+        &[],
+    );
+
+    let last_id = LAST_EXPORTED_ID.load(Ordering::SeqCst);
+
+    let rows: Vec<(i64, String, String, String, Option<String>, String, Option<String>)> = {
+        let guard = AUDIT_DB.lock().map_err(|_| "Не удалось заблокировать мьютекс базы аудита".to_string())?;
+        let conn = guard.as_ref().ok_or_else(|| "База данных аудита не инициализирована".to_string())?;
+
+        let mut stmt = conn
+            .prepare("SELECT id, timestamp, level, action, host, message, user FROM audit_log WHERE id > ?1 ORDER BY id ASC")
+            .map_err(|e| format!("Failed to prepare export query: {}", e))?;
+
+        let mapped = stmt
+            .query_map(rusqlite::params![last_id], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, String>(5)?,
+                    row.get::<_, Option<String>>(6)?,
+                ))
+            })
+            .map_err(|e| format!("Failed to execute export query: {}", e))?;
+
+        let mut out = Vec::new();
+        for row in mapped {
+            out.push(row.map_err(|e| format!("Failed to read export row: {}", e))?);
+        }
+        out
+    };
+
+    let mut exported = 0usize;
+    let mut max_id = last_id;
+    let insert_sql = format!(
+        "INSERT INTO {table} (id, timestamp, level, action, host, message, \"user\") VALUES ($1, $2::timestamptz, $3, $4, $5, $6, $7) ON CONFLICT (id) DO NOTHING",
+        table = hypertable
+    );
+
+    for (id, timestamp, level, action, host, message, user) in rows {
+        client
+            .execute(insert_sql.as_str(), &[&id, &timestamp, &level, &action, &host, &message, &user])
+            .map_err(|e| format!("Failed to insert audit row {} into timeseries sink: {}", id, e))?;
+        max_id = max_id.max(id);
+        exported += 1;
+    }
+
+    LAST_EXPORTED_ID.store(max_id, Ordering::SeqCst);
+
+    Ok(exported)
+}
+
+// Останавливает текущий воркер удаленного приемника (если есть) и, если передана
+// конфигурация, запускает новый со свежим подключением. Вызывается из
+// `update_audit_settings`, а также один раз из `init_audit_log`, если настройки
+// уже содержат `remote_sink` на момент старта
+fn reconfigure_remote_sink(config: Option<RemoteSinkConfig>) {
+    if let Ok(mut guard) = REMOTE_SINK_HANDLE.lock() {
+        if let Some(old) = guard.take() {
+            old.stop_flag.store(true, Ordering::SeqCst);
+        }
+        if let Some(config) = config {
+            *guard = Some(spawn_remote_sink_worker(config));
+        }
+    }
+}
+
+// Неблокирующая отправка записи в очередь воркера удаленного приемника. Переполнение
+// канала или отсутствие настроенного приемника не считаются ошибкой - аудит в файл/SQLite
+// уже сохранен, удаленная отправка является лучшим усилием и не должна тормозить log_action
+fn try_send_to_remote_sink(entry: AuditLog) {
+    if let Ok(guard) = REMOTE_SINK_HANDLE.lock() {
+        if let Some(ref handle) = *guard {
+            match handle.tx.try_send(entry) {
+                Ok(()) => {}
+                Err(mpsc::TrySendError::Full(_)) => {
+                    log::warn!("Очередь удаленного приемника аудита переполнена, запись пропущена");
+                }
+                Err(mpsc::TrySendError::Disconnected(_)) => {
+                    log::warn!("Воркер удаленного приемника аудита недоступен, запись пропущена");
+                }
+            }
+        }
+    }
+}
+
+// Запускает фоновый поток-воркер, владеющий собственным Postgres-подключением и
+// накапливающий записи в буфер, сбрасываемый по `batch_size` или по таймеру
+// `flush_interval_secs`. При ошибке подключения/вставки записи НЕ теряются - воркер
+// хранит их в буфере и повторяет попытку с экспоненциальной задержкой.
+fn spawn_remote_sink_worker(config: RemoteSinkConfig) -> RemoteSinkHandle {
+    let (tx, rx) = mpsc::sync_channel::<AuditLog>(REMOTE_SINK_CHANNEL_CAPACITY);
+    let stop_flag = std::sync::Arc::new(AtomicBool::new(false));
+    let worker_stop_flag = stop_flag.clone();
+
+    std::thread::spawn(move || {
+        let mut client: Option<postgres::Client> = None;
+        let mut buffer: Vec<AuditLog> = Vec::new();
+        let mut last_flush = Instant::now();
+        let mut backoff_secs = 1u64;
+
+        while !worker_stop_flag.load(Ordering::SeqCst) {
+            // Ждём следующую запись максимум до конца текущего интервала сброса, чтобы
+            // буфер сбрасывался по таймеру, даже если новых записей не приходит
+            let wait_for = Duration::from_secs(config.flush_interval_secs)
+                .saturating_sub(last_flush.elapsed());
+            match rx.recv_timeout(wait_for.max(Duration::from_millis(100))) {
+                Ok(entry) => buffer.push(entry),
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+
+            let should_flush = buffer.len() >= config.batch_size
+                || (!buffer.is_empty() && last_flush.elapsed() >= Duration::from_secs(config.flush_interval_secs));
+
+            if !should_flush {
+                continue;
+            }
+
+            if client.is_none() {
+                client = connect_remote_sink(&config);
+            }
+
+            match client.as_mut() {
+                Some(conn) => match flush_remote_sink_batch(conn, &config, &buffer) {
+                    Ok(()) => {
+                        buffer.clear();
+                        last_flush = Instant::now();
+                        backoff_secs = 1;
+                    }
+                    Err(e) => {
+                        log::warn!("Не удалось записать пакет в удаленный приемник аудита: {}", e);
+                        client = None;
+                        sleep_with_backoff(&worker_stop_flag, &mut backoff_secs);
+                    }
+                },
+                None => {
+                    log::warn!("Удаленный приемник аудита недоступен, записи остаются в очереди ({})", buffer.len());
+                    sleep_with_backoff(&worker_stop_flag, &mut backoff_secs);
                 }
-                
-                return logs;
             }
         }
+    });
+
+    RemoteSinkHandle { tx, stop_flag }
+}
+
+fn connect_remote_sink(config: &RemoteSinkConfig) -> Option<postgres::Client> {
+    match postgres::Client::connect(&config.connection_string, postgres::NoTls) {
+        Ok(mut conn) => {
+            let create_table_sql = format!(
+                "CREATE TABLE IF NOT EXISTS {table} (
+                    id BIGSERIAL PRIMARY KEY,
+                    timestamp TIMESTAMPTZ NOT NULL,
+                    level TEXT NOT NULL,
+                    action TEXT NOT NULL,
+                    details TEXT NOT NULL,
+                    \"user\" TEXT,
+                    session_id TEXT NOT NULL
+                )",
+                table = config.table
+            );
+            if let Err(e) = conn.execute(create_table_sql.as_str(), &[]) {
+                log::error!("Failed to create remote audit sink table: {}", e);
+                return None;
+            }
+            // Превращаем в гипертаблицу, если доступно TimescaleDB; если расширения нет,
+            // продолжаем писать в обычную таблицу Postgres
+            let _ = conn.execute(
+                format!("SELECT create_hypertable('{}', 'timestamp', if_not_exists => TRUE)", config.table).as_str(),
+                &[],
+            );
+            Some(conn)
+        }
+        Err(e) => {
+            log::error!("Failed to connect to remote audit sink: {}", e);
+            None
+        }
+    }
+}
+
+fn flush_remote_sink_batch(conn: &mut postgres::Client, config: &RemoteSinkConfig, buffer: &[AuditLog]) -> Result<(), String> {
+    let insert_sql = format!(
+        "INSERT INTO {table} (timestamp, level, action, details, \"user\", session_id) VALUES ($1, $2, $3, $4, $5, $6)",
+        table = config.table
+    );
+    let mut transaction = conn.transaction().map_err(|e| e.to_string())?;
+    for entry in buffer {
+        let timestamp = DateTime::parse_from_rfc3339(&entry.timestamp)
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now());
+        transaction
+            .execute(insert_sql.as_str(), &[&timestamp, &entry.level, &entry.action, &entry.details, &entry.user, &session_id()])
+            .map_err(|e| e.to_string())?;
+    }
+    transaction.commit().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// Экспоненциальная задержка с ограничением сверху, прерываемая флагом остановки -
+// тот же паттерн, что и `interruptible_sleep` в watch.rs/tunnel.rs
+fn sleep_with_backoff(stop_flag: &std::sync::Arc<AtomicBool>, backoff_secs: &mut u64) {
+    let step = Duration::from_millis(100);
+    let mut remaining = Duration::from_secs(*backoff_secs);
+    while remaining > Duration::ZERO {
+        if stop_flag.load(Ordering::SeqCst) {
+            break;
+        }
+        let sleep_time = remaining.min(step);
+        std::thread::sleep(sleep_time);
+        remaining = remaining.saturating_sub(sleep_time);
     }
-    Vec::new()
+    *backoff_secs = (*backoff_secs * 2).min(REMOTE_SINK_MAX_BACKOFF_SECS);
 }
 
 pub fn clear_audit_logs() -> Result<(), String> {
+    if let Ok(guard) = AUDIT_DB.lock() {
+        if let Some(ref conn) = *guard {
+            let _ = conn.execute("DELETE FROM audit_log", []);
+        }
+    }
+
     if let Ok(guard) = AUDIT_FILE.lock() {
         if let Some(ref log_path) = *guard {
             // Создаем новый пустой файл, перезаписывая старый
@@ -296,6 +1053,7 @@ pub fn clear_audit_logs() -> Result<(), String> {
                 if let Ok(json) = serde_json::to_string(&log_entry) {
                     let _ = writeln!(file, "{}", json);
                 }
+                insert_audit_log_db(&log_entry);
                 Ok(())
             } else {
                 Err("Не удалось открыть файл журнала для очистки".to_string())
@@ -0,0 +1,312 @@
+use crate::command_validation::sanitize_command_for_logging;
+use crate::error::{AppError, AppResult};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const AUDIT_LOG_FILE_NAME: &str = "command_audit.log";
+const AUDIT_KEY_FILE_NAME: &str = "command_audit.key";
+/// Порог ротации - как и в общем журнале аудита, разумный компромисс между
+/// размером файла и частотой ротации для текстового JSON-lines лога.
+const MAX_LOG_SIZE_BYTES: u64 = 10 * 1024 * 1024;
+/// Сколько прошлых файлов (`command_audit.log.1` .. `.N`) хранить помимо текущего.
+const MAX_ROTATED_FILES: u32 = 5;
+/// "Genesis"-значение prev_hash для самой первой записи в цепочке.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+static LOG_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+static AUDIT_KEY: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+/// Хэш последней записанной строки - отправная точка цепочки для следующей записи.
+/// Восстанавливается из хвоста файла при инициализации, чтобы цепочка переживала
+/// перезапуски приложения.
+static LAST_HASH: Mutex<String> = Mutex::new(String::new());
+
+/// Одна запись о выполнении команды: что, где, с каким результатом.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandAuditEntry {
+    pub timestamp: String,
+    pub host: String,
+    pub command: String,
+    pub validation_outcome: String,
+    pub exit_status: Option<i32>,
+}
+
+/// Строка на диске - запись плюс хэш-цепочка, делающая журнал защищенным от
+/// незаметного изменения задним числом (удаление/правка строки ломает цепочку).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CommandAuditLine {
+    entry: CommandAuditEntry,
+    prev_hash: String,
+    hmac: String,
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Загружает ключ HMAC-цепочки из `app_data_dir`, либо создает новый случайный
+/// (32 байта) и сохраняет его с правами 0600 - аналогично `encryption.key` в `security.rs`.
+fn load_or_create_audit_key(app_data_dir: &Path) -> Vec<u8> {
+    let key_path = app_data_dir.join(AUDIT_KEY_FILE_NAME);
+
+    if let Ok(bytes) = fs::read(&key_path) {
+        if bytes.len() == 32 {
+            return bytes;
+        }
+        log::warn!("Файл ключа журнала команд поврежден, генерируем новый");
+    }
+
+    let mut key = vec![0u8; 32];
+    use rand::RngCore;
+    rand::thread_rng().fill_bytes(&mut key);
+
+    if let Err(e) = fs::write(&key_path, &key) {
+        log::error!("Failed to write command audit key: {}", e);
+    } else {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if let Ok(mut perms) = fs::metadata(&key_path).map(|m| m.permissions()) {
+                perms.set_mode(0o600);
+                let _ = fs::set_permissions(&key_path, perms);
+            }
+        }
+    }
+
+    key
+}
+
+/// Читает хэш последней валидной строки одного файла лога.
+fn last_line_hash_in_file(path: &Path) -> Option<String> {
+    let file = fs::File::open(path).ok()?;
+    let reader = BufReader::new(file);
+    let mut last_hash = None;
+    for line in reader.lines().map_while(Result::ok) {
+        if let Ok(parsed) = serde_json::from_str::<CommandAuditLine>(&line) {
+            last_hash = Some(parsed.hmac);
+        }
+    }
+    last_hash
+}
+
+/// Читает хэш последней валидной строки лога (для продолжения цепочки после перезапуска).
+/// Сразу после ротации текущий файл (`command_audit.log`) пуст или свежесоздан, а самая
+/// свежая запись цепочки лежит в `command_audit.log.1` - той же логике порядка файлов,
+/// что и в `verify_audit_log`, только нужен только самый свежий хвост, поэтому идем от
+/// текущего файла к более старым ротированным и останавливаемся на первом непустом.
+fn last_line_hash(log_path: &Path) -> Option<String> {
+    if let Some(hash) = last_line_hash_in_file(log_path) {
+        return Some(hash);
+    }
+
+    for i in 1..=MAX_ROTATED_FILES {
+        if let Some(hash) = last_line_hash_in_file(&rotated_path(log_path, i)) {
+            return Some(hash);
+        }
+    }
+
+    None
+}
+
+/// Инициализирует путь к журналу выполнения команд и ключ цепочки.
+/// Вызывается один раз из `main.rs::setup`, аналогично `audit::init_audit_log`.
+pub fn init_command_audit_log(app_data_dir: Option<PathBuf>) {
+    let Some(app_data_dir) = app_data_dir else {
+        log::warn!("app_data_dir недоступен - журнал выполнения команд не будет сохраняться между сессиями");
+        return;
+    };
+
+    if let Err(e) = fs::create_dir_all(&app_data_dir) {
+        log::error!("Failed to create app data dir for command audit log: {}", e);
+    }
+
+    let log_path = app_data_dir.join(AUDIT_LOG_FILE_NAME);
+    let last_hash = last_line_hash(&log_path).unwrap_or_else(|| GENESIS_HASH.to_string());
+    let key = load_or_create_audit_key(&app_data_dir);
+
+    if let Ok(mut guard) = LOG_PATH.lock() {
+        *guard = Some(log_path);
+    }
+    if let Ok(mut guard) = AUDIT_KEY.lock() {
+        *guard = Some(key);
+    }
+    if let Ok(mut guard) = LAST_HASH.lock() {
+        *guard = last_hash;
+    }
+}
+
+fn rotated_path(log_path: &Path, index: u32) -> PathBuf {
+    let mut name = log_path.as_os_str().to_os_string();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+/// Ротация по размеру в стиле logrotate: `command_audit.log` -> `.1` -> `.2` ...,
+/// самый старый файл сверх `MAX_ROTATED_FILES` удаляется.
+fn rotate_if_needed(log_path: &Path) -> std::io::Result<()> {
+    let size = fs::metadata(log_path).map(|m| m.len()).unwrap_or(0);
+    if size < MAX_LOG_SIZE_BYTES {
+        return Ok(());
+    }
+
+    let oldest = rotated_path(log_path, MAX_ROTATED_FILES);
+    if oldest.exists() {
+        fs::remove_file(&oldest)?;
+    }
+
+    for i in (1..MAX_ROTATED_FILES).rev() {
+        let from = rotated_path(log_path, i);
+        if from.exists() {
+            fs::rename(&from, rotated_path(log_path, i + 1))?;
+        }
+    }
+
+    fs::rename(log_path, rotated_path(log_path, 1))?;
+    Ok(())
+}
+
+fn append_entry(entry: CommandAuditEntry) -> Result<(), String> {
+    let log_path = LOG_PATH
+        .lock()
+        .map_err(|e| format!("Failed to lock command audit log path: {}", e))?
+        .clone()
+        .ok_or("Журнал выполнения команд не инициализирован")?;
+    let key = AUDIT_KEY
+        .lock()
+        .map_err(|e| format!("Failed to lock command audit key: {}", e))?
+        .clone()
+        .ok_or("Ключ журнала выполнения команд не инициализирован")?;
+    let mut last_hash_guard = LAST_HASH
+        .lock()
+        .map_err(|e| format!("Failed to lock command audit chain state: {}", e))?;
+
+    let entry_bytes =
+        serde_json::to_vec(&entry).map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+
+    let mut mac = HmacSha256::new_from_slice(&key)
+        .map_err(|e| format!("Invalid command audit HMAC key: {}", e))?;
+    mac.update(last_hash_guard.as_bytes());
+    mac.update(&entry_bytes);
+    let hash = hex_encode(&mac.finalize().into_bytes());
+
+    let line = CommandAuditLine {
+        entry,
+        prev_hash: last_hash_guard.clone(),
+        hmac: hash.clone(),
+    };
+
+    rotate_if_needed(&log_path).map_err(|e| format!("Failed to rotate command audit log: {}", e))?;
+
+    let json = serde_json::to_string(&line)
+        .map_err(|e| format!("Failed to serialize audit log line: {}", e))?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .map_err(|e| format!("Failed to open command audit log: {}", e))?;
+    writeln!(file, "{}", json).map_err(|e| format!("Failed to write command audit log: {}", e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(mut perms) = fs::metadata(&log_path).map(|m| m.permissions()) {
+            perms.set_mode(0o600);
+            let _ = fs::set_permissions(&log_path, perms);
+        }
+    }
+
+    *last_hash_guard = hash;
+    Ok(())
+}
+
+/// Фиксирует в журнале факт выполнения (или попытки выполнения) команды на хосте.
+/// Команда санитизируется тем же механизмом, что используется для обычных логов
+/// (`command_validation::sanitize_command_for_logging`), поэтому пароли/токены
+/// в команде на диск не попадают. Ошибки записи в журнал не прерывают выполнение
+/// команды - это вспомогательный, а не основной путь выполнения.
+pub fn record_command_execution(
+    host: &str,
+    command: &str,
+    validation_outcome: &str,
+    exit_status: Option<i32>,
+) {
+    let entry = CommandAuditEntry {
+        timestamp: Utc::now().to_rfc3339(),
+        host: host.to_string(),
+        command: sanitize_command_for_logging(command),
+        validation_outcome: validation_outcome.to_string(),
+        exit_status,
+    };
+
+    if let Err(e) = append_entry(entry) {
+        log::error!("Failed to append command audit log entry: {}", e);
+    }
+}
+
+/// Проходит по всей цепочке (текущий файл плюс ротированные, от самого старого
+/// к самому новому) и проверяет, что `prev_hash`/`hmac` каждой строки согласованы
+/// с предыдущей. Возвращает индекс первой нарушенной записи (считая от начала
+/// цепочки, 0 - первая запись), либо `None`, если цепочка цела.
+pub fn verify_audit_log() -> AppResult<Option<usize>> {
+    let log_path = LOG_PATH
+        .lock()
+        .map_err(|e| AppError::SecurityError(format!("Failed to lock command audit log path: {}", e)))?
+        .clone()
+        .ok_or_else(|| AppError::SecurityError("Журнал выполнения команд не инициализирован".to_string()))?;
+    let key = AUDIT_KEY
+        .lock()
+        .map_err(|e| AppError::SecurityError(format!("Failed to lock command audit key: {}", e)))?
+        .clone()
+        .ok_or_else(|| AppError::SecurityError("Ключ журнала выполнения команд не инициализирован".to_string()))?;
+
+    let mut files: Vec<PathBuf> = (1..=MAX_ROTATED_FILES)
+        .rev()
+        .map(|i| rotated_path(&log_path, i))
+        .filter(|p| p.exists())
+        .collect();
+    files.push(log_path);
+
+    let mut expected_prev = GENESIS_HASH.to_string();
+    let mut index = 0usize;
+
+    for path in files {
+        let file = fs::File::open(&path)
+            .map_err(|e| AppError::SecurityError(format!("Failed to open {}: {}", path.display(), e)))?;
+        for line in BufReader::new(file).lines().map_while(Result::ok) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let parsed: CommandAuditLine = serde_json::from_str(&line)
+                .map_err(|_| AppError::SecurityError(format!("Не удалось разобрать строку журнала #{}", index)))?;
+
+            if parsed.prev_hash != expected_prev {
+                return Ok(Some(index));
+            }
+
+            let entry_bytes = serde_json::to_vec(&parsed.entry)
+                .map_err(|e| AppError::SecurityError(format!("Failed to re-serialize entry #{}: {}", index, e)))?;
+            let mut mac = HmacSha256::new_from_slice(&key)
+                .map_err(|e| AppError::SecurityError(format!("Invalid command audit HMAC key: {}", e)))?;
+            mac.update(parsed.prev_hash.as_bytes());
+            mac.update(&entry_bytes);
+            let expected_hmac = hex_encode(&mac.finalize().into_bytes());
+
+            if expected_hmac != parsed.hmac {
+                return Ok(Some(index));
+            }
+
+            expected_prev = parsed.hmac;
+            index += 1;
+        }
+    }
+
+    Ok(None)
+}
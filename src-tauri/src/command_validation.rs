@@ -1,35 +1,13 @@
 use crate::error::{AppError, AppResult};
+use crate::ssh::SshFamily;
 use regex::Regex;
-use std::sync::OnceLock;
 
-// Кэшируем регулярные выражения для производительности
-static ENV_VAR_REGEX: OnceLock<Regex> = OnceLock::new();
-static REDIRECT_REGEX: OnceLock<Regex> = OnceLock::new();
-
-fn get_env_var_regex() -> &'static Regex {
-    ENV_VAR_REGEX.get_or_init(|| {
-        Regex::new(r"\$\{?[A-Za-z_][A-Za-z0-9_]*\}?")
-            .expect("ENV_VAR_REGEX pattern is invalid")
-    })
-}
-
-fn get_redirect_regex() -> &'static Regex {
-    REDIRECT_REGEX.get_or_init(|| {
-        Regex::new(r"[<>]\s*[0-9]*")
-            .expect("REDIRECT_REGEX pattern is invalid")
-    })
-}
-
-// Список опасных символов и операторов, которые могут использоваться для инъекции команд
-const DANGEROUS_CHARS: &[&str] = &[
-    ";", "|", "&", "&&", "||", ">", "<", ">>", "<<", "`", "$", "(", ")", "{", "}",
-    "\n", "\r", "\t", "\\", "'", "\"", "#", "*", "?", "[", "]",
-];
-
-// Черный список опасных команд (базовые имена команд)
+// Черный список опасных команд (базовые имена команд), запрещенных полностью -
+// независимо от аргументов. Команды, для которых опасны лишь отдельные формы
+// вызова (например, `dd`), сюда не входят - для них ниже заведены точечные
+// `ArgumentRule` в политике конкретной команды.
 const DANGEROUS_COMMANDS: &[&str] = &[
     "rm",      // Удаление файлов
-    "dd",      // Копирование/уничтожение данных
     "mkfs",    // Форматирование файловых систем
     "fdisk",   // Разметка дисков
     "parted",  // Разметка дисков
@@ -45,18 +23,20 @@ const DANGEROUS_COMMANDS: &[&str] = &[
     "del",     // Удаление (Windows, но может быть в Linux как алиас)
 ];
 
-// Опасные аргументы, которые могут использоваться с командами
-const DANGEROUS_ARGUMENTS: &[&str] = &[
-    "-rf",     // Рекурсивное принудительное удаление
-    "-r -f",   // Рекурсивное принудительное удаление (раздельно)
-    "-f -r",   // Рекурсивное принудительное удаление (раздельно, обратный порядок)
-    "/",       // Корневая директория
-    "/dev/",   // Устройства
-    "/proc/",  // Виртуальная файловая система
-    "/sys/",   // Виртуальная файловая система
-    "of=/dev/", // Для dd - запись в устройство
-    "if=/dev/zero", // Для dd - чтение из /dev/zero
-    "if=/dev/urandom", // Для dd - чтение случайных данных
+// Черный список опасных команд для Windows-хостов (cmd.exe / PowerShell)
+const DANGEROUS_COMMANDS_WINDOWS: &[&str] = &[
+    "format",     // Форматирование дисков
+    "del",        // Удаление файлов
+    "erase",      // Удаление файлов
+    "rd",         // Удаление директорий (rmdir)
+    "rmdir",      // Удаление директорий
+    "shutdown",   // Выключение/перезагрузка
+    "diskpart",   // Управление разделами диска
+    "taskkill",   // Завершение процессов
+    "reg",        // Редактирование реестра
+    "vssadmin",   // Управление теневыми копиями (часто используется для их удаления)
+    "cipher",     // При определенных флагах безвозвратно затирает свободное место
+    "net",        // Управление учетными записями/службами
 ];
 
 // Максимальная длина команды (предотвращение DoS)
@@ -65,21 +45,479 @@ const MAX_COMMAND_LENGTH: usize = 10000;
 // Минимальная длина команды (предотвращение пустых команд)
 const MIN_COMMAND_LENGTH: usize = 1;
 
-/// Валидирует команду перед выполнением
-/// 
+/// Оператор shell, распознанный токенайзером как самостоятельный узел разбора
+/// (а не как "опасный символ" где-то в середине строки).
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Operator {
+    Pipe,
+    Semicolon,
+    And,
+    Or,
+    Background,
+    RedirectOut,
+    RedirectAppend,
+    RedirectIn,
+}
+
+impl Operator {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Operator::Pipe => "|",
+            Operator::Semicolon => ";",
+            Operator::And => "&&",
+            Operator::Or => "||",
+            Operator::Background => "&",
+            Operator::RedirectOut => ">",
+            Operator::RedirectAppend => ">>",
+            Operator::RedirectIn => "<",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Op(Operator),
+}
+
+fn flush_word(word: &mut String, in_word: &mut bool, tokens: &mut Vec<Token>) {
+    if *in_word {
+        tokens.push(Token::Word(std::mem::take(word)));
+        *in_word = false;
+    }
+}
+
+/// Разбивает команду на слова и операторы по правилам, близким к POSIX shell:
+/// одинарные/двойные кавычки и `\` экранируют разделители слов, а `;`, `|`,
+/// `&&`, `||`, `&`, `>`, `>>`, `<` распознаются как отдельные узлы, а не как
+/// "запрещенные символы" где-то внутри строки. Подстановка команд (`` `..` ``,
+/// `$(...)`), раскрытие переменных окружения (`$VAR`) и группировка (`(`, `)`,
+/// `{`, `}`) не поддерживаются вовсе, так как их содержимое невозможно
+/// провалидировать на уровне argv - такие конструкции запрещены безусловно.
+fn tokenize(command: &str) -> AppResult<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = command.chars().peekable();
+    let mut word = String::new();
+    let mut in_word = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' => flush_word(&mut word, &mut in_word, &mut tokens),
+            '\n' | '\r' => {
+                return Err(AppError::SecurityError(
+                    "Многострочные команды запрещены для безопасности".to_string(),
+                ));
+            }
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(ch) => word.push(ch),
+                        None => {
+                            return Err(AppError::SecurityError(
+                                "Незакрытая одинарная кавычка в команде".to_string(),
+                            ))
+                        }
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('$') => {
+                            return Err(AppError::SecurityError(
+                                "Использование переменных окружения в командах запрещено для безопасности".to_string(),
+                            ))
+                        }
+                        Some('\\') => match chars.next() {
+                            Some(next @ ('"' | '\\')) => word.push(next),
+                            Some(next) => {
+                                word.push('\\');
+                                word.push(next);
+                            }
+                            None => {
+                                return Err(AppError::SecurityError(
+                                    "Незакрытая двойная кавычка в команде".to_string(),
+                                ))
+                            }
+                        },
+                        Some(ch) => word.push(ch),
+                        None => {
+                            return Err(AppError::SecurityError(
+                                "Незакрытая двойная кавычка в команде".to_string(),
+                            ))
+                        }
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                match chars.next() {
+                    Some(next) => word.push(next),
+                    None => {
+                        return Err(AppError::SecurityError(
+                            "Команда не может заканчиваться символом экранирования '\\'".to_string(),
+                        ))
+                    }
+                }
+            }
+            '`' => {
+                return Err(AppError::SecurityError(
+                    "Подстановка команд (`...`) запрещена для безопасности".to_string(),
+                ))
+            }
+            '$' => {
+                if chars.peek() == Some(&'(') {
+                    return Err(AppError::SecurityError(
+                        "Подстановка команд ($(...)) запрещена для безопасности".to_string(),
+                    ));
+                }
+                return Err(AppError::SecurityError(
+                    "Использование переменных окружения в командах запрещено для безопасности".to_string(),
+                ));
+            }
+            '(' | ')' | '{' | '}' => {
+                return Err(AppError::SecurityError(format!(
+                    "Конструкция группировки команд '{}' запрещена для безопасности",
+                    c
+                )))
+            }
+            '#' => {
+                return Err(AppError::SecurityError(
+                    "Комментарии в командах запрещены для безопасности".to_string(),
+                ))
+            }
+            ';' => {
+                flush_word(&mut word, &mut in_word, &mut tokens);
+                tokens.push(Token::Op(Operator::Semicolon));
+            }
+            '|' => {
+                flush_word(&mut word, &mut in_word, &mut tokens);
+                if chars.peek() == Some(&'|') {
+                    chars.next();
+                    tokens.push(Token::Op(Operator::Or));
+                } else {
+                    tokens.push(Token::Op(Operator::Pipe));
+                }
+            }
+            '&' => {
+                flush_word(&mut word, &mut in_word, &mut tokens);
+                if chars.peek() == Some(&'&') {
+                    chars.next();
+                    tokens.push(Token::Op(Operator::And));
+                } else {
+                    tokens.push(Token::Op(Operator::Background));
+                }
+            }
+            '>' => {
+                flush_word(&mut word, &mut in_word, &mut tokens);
+                if chars.peek() == Some(&'>') {
+                    chars.next();
+                    tokens.push(Token::Op(Operator::RedirectAppend));
+                } else {
+                    tokens.push(Token::Op(Operator::RedirectOut));
+                }
+            }
+            '<' => {
+                flush_word(&mut word, &mut in_word, &mut tokens);
+                tokens.push(Token::Op(Operator::RedirectIn));
+            }
+            _ => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    flush_word(&mut word, &mut in_word, &mut tokens);
+
+    Ok(tokens)
+}
+
+/// Одна простая команда из конвейера - argv плюс признак того, что у нее есть
+/// перенаправление ввода/вывода (само целевое имя файла в argv не попадает).
+struct SimpleCommand {
+    argv: Vec<String>,
+    has_redirect: bool,
+}
+
+/// Одно "предложение" команды - конвейер из одной или нескольких простых команд,
+/// разделенных `|`.
+struct Statement {
+    pipeline: Vec<SimpleCommand>,
+    uses_pipe: bool,
+}
+
+fn parse_simple_command(tokens: Vec<Token>) -> AppResult<SimpleCommand> {
+    let mut argv = Vec::new();
+    let mut has_redirect = false;
+    let mut iter = tokens.into_iter().peekable();
+
+    while let Some(tok) = iter.next() {
+        match tok {
+            Token::Word(w) => argv.push(w),
+            Token::Op(Operator::RedirectOut)
+            | Token::Op(Operator::RedirectAppend)
+            | Token::Op(Operator::RedirectIn) => {
+                has_redirect = true;
+                // Имя файла-цели перенаправления - не аргумент команды, пропускаем его
+                if let Some(Token::Word(_)) = iter.peek() {
+                    iter.next();
+                }
+            }
+            Token::Op(op) => {
+                return Err(AppError::SecurityError(format!(
+                    "Оператор '{}' не может находиться внутри одной команды",
+                    op.symbol()
+                )))
+            }
+        }
+    }
+
+    if argv.is_empty() {
+        return Err(AppError::SecurityError(
+            "Команда не может быть пустой".to_string(),
+        ));
+    }
+
+    Ok(SimpleCommand { argv, has_redirect })
+}
+
+fn parse_statement(tokens: Vec<Token>) -> AppResult<Statement> {
+    let mut pipeline = Vec::new();
+    let mut current = Vec::new();
+
+    for tok in tokens {
+        if tok == Token::Op(Operator::Pipe) {
+            pipeline.push(parse_simple_command(std::mem::take(&mut current))?);
+        } else {
+            current.push(tok);
+        }
+    }
+    pipeline.push(parse_simple_command(current)?);
+
+    let uses_pipe = pipeline.len() > 1;
+    Ok(Statement { pipeline, uses_pipe })
+}
+
+/// Разбивает поток токенов на отдельные "предложения" по операторам
+/// последовательности (`;`, `&&`, `||`, `&`), возвращая также сам список этих
+/// операторов - политика решает, какие из них допустимы.
+fn parse_statements(tokens: Vec<Token>) -> AppResult<(Vec<Statement>, Vec<Operator>)> {
+    let mut statements = Vec::new();
+    let mut separators = Vec::new();
+    let mut current = Vec::new();
+
+    for tok in tokens {
+        match tok {
+            Token::Op(op @ (Operator::Semicolon | Operator::And | Operator::Or | Operator::Background)) => {
+                statements.push(parse_statement(std::mem::take(&mut current))?);
+                separators.push(op);
+            }
+            other => current.push(other),
+        }
+    }
+
+    if current.is_empty() {
+        if separators.last() != Some(&Operator::Background) {
+            return Err(AppError::SecurityError(
+                "Команда обрывается на операторе, ожидается продолжение".to_string(),
+            ));
+        }
+    } else {
+        statements.push(parse_statement(current)?);
+    }
+
+    Ok((statements, separators))
+}
+
+/// Точечное правило для аргументов конкретной команды - используется там, где
+/// полный запрет команды избыточен (например, `dd` с безопасными параметрами
+/// копирования файлов должна работать, а `dd of=/dev/sda` - нет).
+enum ArgumentRule {
+    /// Запрещает аргумент, содержащий данную подстроку (без учета регистра).
+    DeniedSubstring(&'static str),
+}
+
+/// Политика валидации команд для одного семейства ОС - аналог allowlist-style
+/// политик безопасности (вроде `StandardPolicy` у OpenPGP-библиотек): вместо
+/// плоского черного списка символов здесь явно перечислено, что именно
+/// запрещено на уровне распарсенной команды.
+struct CommandPolicy {
+    /// Исполняемые файлы, запрещенные полностью, независимо от аргументов.
+    denied_executables: &'static [&'static str],
+    /// Аргументы, запрещенные для любой команды (точное совпадение, без учета регистра).
+    denied_arguments: &'static [&'static str],
+    /// Пары аргументов, запрещенные в сочетании (в любом порядке), например `-r` и `-f`.
+    denied_argument_combos: &'static [(&'static str, &'static str)],
+    /// Префиксы путей, запрещенные в качестве аргумента любой команды (устройства, /proc, /sys).
+    denied_path_prefixes: &'static [&'static str],
+    /// Точечные правила для аргументов конкретных команд.
+    argument_rules: &'static [(&'static str, &'static [ArgumentRule])],
+    allow_pipelines: bool,
+    allow_chaining: bool,
+    allow_background: bool,
+    allow_redirects: bool,
+}
+
+const UNIX_POLICY: CommandPolicy = CommandPolicy {
+    denied_executables: DANGEROUS_COMMANDS,
+    denied_arguments: &["-rf"],
+    denied_argument_combos: &[("-r", "-f")],
+    denied_path_prefixes: &["/dev", "/proc", "/sys"],
+    argument_rules: &[(
+        "dd",
+        &[
+            ArgumentRule::DeniedSubstring("of=/dev/sd"),
+            ArgumentRule::DeniedSubstring("of=/dev/hd"),
+        ],
+    )],
+    allow_pipelines: false,
+    allow_chaining: false,
+    allow_background: false,
+    allow_redirects: false,
+};
+
+const WINDOWS_POLICY: CommandPolicy = CommandPolicy {
+    denied_executables: DANGEROUS_COMMANDS_WINDOWS,
+    denied_arguments: &["/s", "/q", "delete"],
+    denied_argument_combos: &[],
+    denied_path_prefixes: &["c:\\windows"],
+    argument_rules: &[],
+    allow_pipelines: false,
+    allow_chaining: false,
+    allow_background: false,
+    allow_redirects: false,
+};
+
+/// Расширения исполняемых файлов Windows, которые cmd.exe/PowerShell находят и
+/// запускают без явного указания - `shutdown.exe` и `shutdown` для них эквивалентны,
+/// поэтому денылист должен ловить обе формы.
+const WINDOWS_EXECUTABLE_EXTENSIONS: &[&str] = &[".exe", ".cmd", ".bat"];
+
+/// Извлекает имя исполняемого файла из первого слова команды, отбрасывая путь
+/// (`/usr/bin/rm` -> `rm`, `./rm` -> `rm`) и, если есть, расширение Windows-исполняемого
+/// файла (`shutdown.exe` -> `shutdown`) - иначе денылист для Windows-хостов сравнивается
+/// с именем, которое на самом деле никогда не встречается в команде.
+fn extract_executable_name(word: &str) -> &str {
+    let base = if word.starts_with('/') || word.starts_with("./") || word.starts_with("../") || word.contains('\\') {
+        word.rsplit(['/', '\\']).next().unwrap_or(word)
+    } else {
+        word
+    };
+
+    let lower = base.to_lowercase();
+    for ext in WINDOWS_EXECUTABLE_EXTENSIONS {
+        if lower.ends_with(ext) {
+            return &base[..base.len() - ext.len()];
+        }
+    }
+
+    base
+}
+
+fn validate_simple_command(cmd: &SimpleCommand, policy: &CommandPolicy) -> AppResult<()> {
+    let executable = extract_executable_name(&cmd.argv[0]).to_lowercase();
+
+    for denied in policy.denied_executables {
+        if executable == *denied {
+            return Err(AppError::SecurityError(format!(
+                "Выполнение команды '{}' запрещено для безопасности. Эта команда может привести к потере данных или нарушению работы системы.",
+                executable
+            )));
+        }
+    }
+
+    let args_lower: Vec<String> = cmd.argv[1..].iter().map(|a| a.to_lowercase()).collect();
+
+    for denied_arg in policy.denied_arguments {
+        if args_lower.iter().any(|a| a == denied_arg) {
+            return Err(AppError::SecurityError(format!(
+                "Команда содержит опасный аргумент '{}', который может привести к потере данных или нарушению работы системы.",
+                denied_arg
+            )));
+        }
+    }
+
+    for (first, second) in policy.denied_argument_combos {
+        if args_lower.iter().any(|a| a == first) && args_lower.iter().any(|a| a == second) {
+            return Err(AppError::SecurityError(format!(
+                "Команда содержит опасную комбинацию аргументов '{} {}', которая может привести к потере данных или нарушению работы системы.",
+                first, second
+            )));
+        }
+    }
+
+    if args_lower.iter().any(|a| a == "/") {
+        return Err(AppError::SecurityError(
+            "Обращение к корневой директории в качестве аргумента запрещено для безопасности".to_string(),
+        ));
+    }
+
+    for prefix in policy.denied_path_prefixes {
+        if args_lower.iter().any(|a| a.starts_with(prefix)) {
+            return Err(AppError::SecurityError(format!(
+                "Команда содержит обращение к защищенному пути, начинающемуся с '{}', что запрещено для безопасности.",
+                prefix
+            )));
+        }
+    }
+
+    if let Some((_, rules)) = policy.argument_rules.iter().find(|(name, _)| *name == executable) {
+        for rule in *rules {
+            match rule {
+                ArgumentRule::DeniedSubstring(substr) => {
+                    if args_lower.iter().any(|a| a.contains(substr)) {
+                        return Err(AppError::SecurityError(format!(
+                            "Команда '{}' содержит аргумент, запрещенный политикой безопасности ('{}').",
+                            executable, substr
+                        )));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Валидирует команду перед выполнением (для Unix-хостов, по умолчанию)
+///
 /// Проверяет:
-/// - Наличие опасных символов
 /// - Длину команды
 /// - Базовую структуру команды
-/// 
+/// - Распарсенную структуру (операторы, конвейеры, перенаправления) и аргументы
+///   против политики безопасности для Unix
+///
 /// # Параметры
 /// * `command` - Команда для валидации
 /// * `skip_validation` - Если true, пропускает все проверки (опасно!)
 pub fn validate_command(command: &str, skip_validation: bool) -> AppResult<()> {
+    validate_command_for_family(command, skip_validation, SshFamily::Unix)
+}
+
+/// Валидирует команду перед выполнением с учетом семейства ОС удаленного хоста.
+///
+/// Команда разбирается shell-aware токенайзером (с учетом кавычек и
+/// экранирования) в дерево из предложений, конвейеров и простых команд с их
+/// argv, после чего полученная структура проверяется против политики
+/// безопасности конкретного семейства ОС (список запрещенных исполняемых
+/// файлов, запрещенные аргументы/пути, точечные правила для отдельных команд,
+/// а также разрешены ли вообще конвейеры, объединение команд, фоновые задачи
+/// и перенаправления).
+///
+/// # Параметры
+/// * `command` - Команда для валидации
+/// * `skip_validation` - Если true, пропускает все проверки (опасно!)
+/// * `family` - Семейство ОС удаленного хоста, определяет какая политика применяется
+pub fn validate_command_for_family(command: &str, skip_validation: bool, family: SshFamily) -> AppResult<()> {
     // Если валидация отключена, пропускаем все проверки
     if skip_validation {
         return Ok(());
     }
+
     // Проверка длины
     if command.len() < MIN_COMMAND_LENGTH {
         return Err(AppError::SecurityError(
@@ -94,35 +532,10 @@ pub fn validate_command(command: &str, skip_validation: bool) -> AppResult<()> {
         )));
     }
 
-    // Проверка на опасные символы
-    for dangerous_char in DANGEROUS_CHARS {
-        if command.contains(dangerous_char) {
-            return Err(AppError::SecurityError(format!(
-                "Команда содержит недопустимый символ: '{}'. Использование специальных символов запрещено для безопасности.",
-                dangerous_char
-            )));
-        }
-    }
-
-    // Проверка на попытки выполнения нескольких команд
     let trimmed = command.trim();
-    if trimmed.contains("  ") {
-        return Err(AppError::SecurityError(
-            "Команда содержит множественные пробелы, что может указывать на попытку инъекции".to_string(),
-        ));
-    }
-
-    // Проверка на попытки использования переменных окружения через $ или ${}
-    if get_env_var_regex().is_match(command) {
+    if trimmed.is_empty() {
         return Err(AppError::SecurityError(
-            "Использование переменных окружения в командах запрещено для безопасности".to_string(),
-        ));
-    }
-
-    // Проверка на попытки перенаправления ввода/вывода
-    if get_redirect_regex().is_match(command) {
-        return Err(AppError::SecurityError(
-            "Перенаправление ввода/вывода запрещено для безопасности".to_string(),
+            "Команда не может быть пустой".to_string(),
         ));
     }
 
@@ -135,59 +548,42 @@ pub fn validate_command(command: &str, skip_validation: bool) -> AppResult<()> {
         }
     }
 
-    // Проверка на опасные команды
-    // Извлекаем базовое имя команды (первое слово или имя файла из пути)
-    let command_parts: Vec<&str> = trimmed.split_whitespace().collect();
-    if let Some(first_part) = command_parts.first() {
-        // Извлекаем имя команды из пути (если есть путь)
-        let command_name = if first_part.starts_with('/') || first_part.starts_with("./") {
-            // Извлекаем имя файла из пути
-            first_part.split('/').last().unwrap_or(first_part)
-        } else {
-            first_part
-        };
+    let tokens = tokenize(trimmed)?;
+    let (statements, separators) = parse_statements(tokens)?;
 
-        // Проверяем, не является ли команда опасной
-        for dangerous_cmd in DANGEROUS_COMMANDS {
-            if command_name == *dangerous_cmd || command_name.ends_with(&format!("/{}", dangerous_cmd)) {
-                return Err(AppError::SecurityError(format!(
-                    "Выполнение команды '{}' запрещено для безопасности. Эта команда может привести к потере данных или нарушению работы системы.",
-                    command_name
-                )));
-            }
-        }
-    }
+    let policy = match family {
+        SshFamily::Unix => &UNIX_POLICY,
+        SshFamily::Windows => &WINDOWS_POLICY,
+    };
 
-    // Проверка на опасные аргументы в команде
-    let command_lower = trimmed.to_lowercase();
-    for dangerous_arg in DANGEROUS_ARGUMENTS {
-        if command_lower.contains(dangerous_arg) {
+    for op in &separators {
+        let allowed = match op {
+            Operator::Background => policy.allow_background,
+            _ => policy.allow_chaining,
+        };
+        if !allowed {
             return Err(AppError::SecurityError(format!(
-                "Команда содержит опасный аргумент '{}', который может привести к потере данных или нарушению работы системы.",
-                dangerous_arg
+                "Использование оператора '{}' для объединения нескольких команд запрещено для безопасности",
+                op.symbol()
             )));
         }
     }
 
-    // Дополнительная проверка: комбинация rm с -rf или -r -f
-    if command_lower.starts_with("rm ") || command_lower.contains("/rm ") {
-        if command_lower.contains("-rf") || command_lower.contains("-r -f") || command_lower.contains("-f -r") {
+    for statement in &statements {
+        if statement.uses_pipe && !policy.allow_pipelines {
             return Err(AppError::SecurityError(
-                "Команда 'rm' с флагами '-rf' запрещена для безопасности. Используйте 'rm' без флага '-f' или удаляйте файлы по одному.".to_string(),
+                "Использование конвейеров (|) запрещено для безопасности".to_string(),
             ));
         }
-    }
 
-    // Дополнительная проверка: команда dd с опасными параметрами
-    if command_lower.starts_with("dd ") || command_lower.contains("/dd ") {
-        if command_lower.contains("of=/dev/") && !command_lower.contains("if=/dev/zero") {
-            // Разрешаем только безопасные операции с dd (например, копирование файлов)
-            // Но блокируем запись в устройства
-            if command_lower.contains("of=/dev/sd") || command_lower.contains("of=/dev/hd") {
+        for simple in &statement.pipeline {
+            if simple.has_redirect && !policy.allow_redirects {
                 return Err(AppError::SecurityError(
-                    "Команда 'dd' с записью в блочные устройства запрещена для безопасности.".to_string(),
+                    "Перенаправление ввода/вывода запрещено для безопасности".to_string(),
                 ));
             }
+
+            validate_simple_command(simple, policy)?;
         }
     }
 
@@ -197,12 +593,12 @@ pub fn validate_command(command: &str, skip_validation: bool) -> AppResult<()> {
 /// Санитизирует команду для логирования (удаляет чувствительные данные)
 pub fn sanitize_command_for_logging(command: &str) -> String {
     let mut sanitized = command.to_string();
-    
+
     // Ограничиваем длину в логах
     if sanitized.len() > 200 {
         sanitized = format!("{}...", &sanitized[..200]);
     }
-    
+
     // Маскируем потенциально чувствительные данные
     // Ищем паттерны типа "password=xxx" или "key=xxx"
     let sensitive_patterns = vec![
@@ -213,13 +609,13 @@ pub fn sanitize_command_for_logging(command: &str) -> String {
         (r"(?i)token\s*=\s*\S+", "token=***"),
         (r"(?i)secret\s*=\s*\S+", "secret=***"),
     ];
-    
+
     for (pattern, replacement) in sensitive_patterns {
         if let Ok(re) = Regex::new(pattern) {
             sanitized = re.replace_all(&sanitized, replacement).to_string();
         }
     }
-    
+
     sanitized
 }
 
@@ -237,6 +633,17 @@ mod tests {
         assert!(validate_command("netstat -tulpn", false).is_ok());
     }
 
+    #[test]
+    fn test_validate_command_quoted_and_globs() {
+        // Кавычки и glob-символы - легитимные конструкции, которые раньше
+        // блокировались посимвольно, а теперь должны проходить валидацию.
+        assert!(validate_command("echo 'hello world'", false).is_ok());
+        assert!(validate_command(r#"grep "error" log.txt"#, false).is_ok());
+        assert!(validate_command("ls *.txt", false).is_ok());
+        assert!(validate_command("ls file?.log", false).is_ok());
+        assert!(validate_command("ls [abc].txt", false).is_ok());
+    }
+
     #[test]
     fn test_validate_command_skip_validation() {
         // При skip_validation=true все команды должны проходить, даже опасные
@@ -304,7 +711,7 @@ mod tests {
         assert!(validate_command("rm -rf directory", false).is_err());
         assert!(validate_command("rm -r -f directory", false).is_err());
         assert!(validate_command("rm -f -r directory", false).is_err());
-        // Но rm без -f должен быть разрешен (хотя сама команда rm все равно заблокирована)
+        // rm полностью запрещена вне зависимости от флагов
     }
 
     #[test]
@@ -313,4 +720,10 @@ mod tests {
         assert!(validate_command("dd if=/dev/zero of=/dev/sda", false).is_err());
         assert!(validate_command("dd if=/dev/zero of=/dev/hda", false).is_err());
     }
+
+    #[test]
+    fn test_validate_command_dd_safe() {
+        // dd без записи в блочное устройство теперь разрешена точечной политикой
+        assert!(validate_command("dd if=backup.img of=restore.img bs=4M", false).is_ok());
+    }
 }
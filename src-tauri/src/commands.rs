@@ -8,9 +8,45 @@ use serde::{Deserialize, Serialize};
 use tauri::{State, Window};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, mpsc};
+use chrono::Utc;
+use rand::Rng;
 
 pub type SshPool = std::sync::Arc<ssh::SshConnectionPool>;
 
+// Кольцевые буферы вывода по хостам, заполняемые при потоковом выполнении пакетных команд
+pub type HostLogBuffers = Arc<parking_lot::Mutex<std::collections::HashMap<String, crate::log_buffer::LogBuffer>>>;
+
+const HOST_LOG_BUFFER_CAPACITY: usize = 500;
+
+/// Максимальная задержка между повторными попытками для одного хоста (секунды),
+/// даже если экспоненциальный рост предлагает больше
+const MAX_RETRY_BACKOFF_SECS: u64 = 300;
+
+/// Текущее состояние планирования повторных попыток для одного хоста, отдается в UI
+/// через `get_retry_schedule`, чтобы можно было показать "повтор хоста X через Nс"
+#[derive(Debug, Clone, Serialize)]
+pub struct RetryInfo {
+    pub host: String,
+    pub error_count: u32,
+    pub last_try: String,
+    pub next_try: String,
+}
+
+// Реестр расписаний повторных попыток по execution_id, заполняется во время
+// выполнения execute_batch_commands и читается командой get_retry_schedule
+pub type RetrySchedules = Arc<parking_lot::Mutex<std::collections::HashMap<u64, Vec<RetryInfo>>>>;
+
+// Событие живого стриминга вывода в потоковом режиме пакетного выполнения.
+// `seq` - монотонный номер строки в рамках одного хоста, чтобы фронтенд мог
+// корректно упорядочить/собрать чанки, приходящие параллельно с разных хостов.
+#[derive(Clone, Serialize)]
+struct BatchOutputEvent {
+    execution_id: u64,
+    host: String,
+    seq: u64,
+    chunk: String,
+}
+
 // Структура для управления отменой выполнения команд
 #[derive(Clone)]
 pub struct CancellationToken {
@@ -43,13 +79,37 @@ impl Default for CancellationToken {
     }
 }
 
+/// `allow_cidrs`/`deny_cidrs` - опциональные списки CIDR-диапазонов (например, `10.0.0.0/8`),
+/// которыми отфильтровываются загруженные хосты (см. `file_parser::IpFilter`); deny имеет
+/// приоритет, а пустой allow означает "разрешено все, что не запрещено". Без обоих списков
+/// фильтрация не применяется.
 #[tauri::command]
 pub async fn parse_hosts_file(
     file_path: String,
+    resolve_dns: Option<bool>,
+    allow_cidrs: Option<Vec<String>>,
+    deny_cidrs: Option<Vec<String>>,
 ) -> Result<Vec<file_parser::HostEntry>, String> {
     audit::log_action("INFO", "parse_hosts", &format!("Загрузка файла: {}", file_path), None);
-    
-    file_parser::parse_hosts_file(&file_path)
+
+    let filter = if allow_cidrs.is_some() || deny_cidrs.is_some() {
+        let parse_all = |cidrs: Option<Vec<String>>| -> Result<Vec<file_parser::CidrRange>, String> {
+            cidrs
+                .unwrap_or_default()
+                .iter()
+                .map(|cidr| file_parser::CidrRange::parse(cidr))
+                .collect()
+        };
+        Some(file_parser::IpFilter {
+            allow: parse_all(allow_cidrs)?,
+            deny: parse_all(deny_cidrs)?,
+        })
+    } else {
+        None
+    };
+
+    file_parser::parse_hosts_file_with_dns(&file_path, resolve_dns.unwrap_or(false), filter.as_ref())
+        .await
         .map_err(|e| e.to_string())
 }
 
@@ -80,9 +140,11 @@ pub async fn execute_ssh_command(
     
     // Валидация команды перед выполнением (если не отключена)
     let skip_val = skip_validation.unwrap_or(false);
-    crate::command_validation::validate_command(&command, skip_val)
-        .map_err(|e| format!("Ошибка валидации команды: {}", e))?;
-    
+    if let Err(e) = crate::command_validation::validate_command(&command, skip_val) {
+        crate::command_audit::record_command_execution(&config.host, &command, "rejected", None);
+        return Err(format!("Ошибка валидации команды: {}", e));
+    }
+
     // Санитизируем команду для логирования
     let sanitized_command = crate::command_validation::sanitize_command_for_logging(&command);
     audit::log_action("INFO", "execute_command", &format!("Выполнение команды на {}: {}", config.host, sanitized_command), None);
@@ -91,23 +153,39 @@ pub async fn execute_ssh_command(
     if cancellation_token.is_cancelled() {
         return Err("Выполнение команды отменено".to_string());
     }
-    
+
+    let host = config.host.clone();
     let connection = pool.get_or_create(config)
         .map_err(|e| e.to_string())?;
-    
+
     // Проверяем отмену перед выполнением команды
     if cancellation_token.is_cancelled() {
         return Err("Выполнение команды отменено".to_string());
     }
-    
-    connection.execute_command(&command)
-        .map_err(|e| {
-            if cancellation_token.is_cancelled() {
-                "Выполнение команды отменено".to_string()
-            } else {
-                e.to_string()
-            }
-        })
+
+    // Повторная валидация с учетом реального семейства ОС хоста
+    // (первичная проверка перед подключением использовала Unix по умолчанию)
+    if let Err(e) = crate::command_validation::validate_command_for_family(&command, skip_val, connection.family()) {
+        crate::command_audit::record_command_execution(&host, &command, "rejected", None);
+        return Err(format!("Ошибка валидации команды: {}", e));
+    }
+
+    let result = connection.execute_command(&command);
+    let validation_outcome = if skip_val { "allowed_unvalidated" } else { "allowed" };
+    crate::command_audit::record_command_execution(
+        &host,
+        &command,
+        validation_outcome,
+        result.as_ref().ok().map(|r| r.exit_status),
+    );
+
+    result.map_err(|e| {
+        if cancellation_token.is_cancelled() {
+            "Выполнение команды отменено".to_string()
+        } else {
+            e.to_string()
+        }
+    })
 }
 
 // Вспомогательная структура для десериализации из фронтенда
@@ -123,6 +201,8 @@ struct BatchCommandRequestHelper {
     pub retry_interval: Option<u64>,
     #[serde(default)]
     pub skip_validation: Option<bool>,
+    #[serde(default)]
+    pub streaming: Option<bool>,
 }
 
 // Копия SshConfigHelper для использования в BatchCommandRequest
@@ -147,6 +227,14 @@ struct SshConfigHelperForBatch {
     compression_enabled: Option<bool>,
     #[serde(default)]
     compression_level: Option<u32>,
+    #[serde(default)]
+    host_key_algorithms: Option<Vec<String>>,
+    #[serde(default)]
+    kex_algorithms: Option<Vec<String>>,
+    #[serde(default)]
+    ciphers: Option<Vec<String>>,
+    #[serde(default)]
+    mac_algorithms: Option<Vec<String>>,
 }
 
 impl From<SshConfigHelperForBatch> for ssh::SshConfig {
@@ -231,6 +319,11 @@ impl From<SshConfigHelperForBatch> for ssh::SshConfig {
             reconnect_delay_base: helper.reconnect_delay_base,
             compression_enabled: helper.compression_enabled,
             compression_level: helper.compression_level,
+            host_key_algorithms: helper.host_key_algorithms,
+            kex_algorithms: helper.kex_algorithms,
+            ciphers: helper.ciphers,
+            mac_algorithms: helper.mac_algorithms,
+            json_field_extractors: None,
         }
     }
 }
@@ -245,6 +338,7 @@ pub struct BatchCommandRequest {
     pub retry_failed_hosts: bool,
     pub retry_interval: u64,
     pub skip_validation: bool,
+    pub streaming: bool,
 }
 
 impl From<BatchCommandRequestHelper> for BatchCommandRequest {
@@ -257,15 +351,81 @@ impl From<BatchCommandRequestHelper> for BatchCommandRequest {
             retry_failed_hosts: helper.retry_failed_hosts.unwrap_or(false),
             retry_interval: helper.retry_interval.unwrap_or(30),
             skip_validation: helper.skip_validation.unwrap_or(false),
+            streaming: helper.streaming.unwrap_or(false),
         }
     }
 }
 
+/// Машинно-читаемая классификация ошибки хоста в пакетном выполнении - позволяет
+/// скриптам агрегировать отказы по категориям, а не сопоставлять локализованный текст
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchErrorKind {
+    KeyMissing,
+    KeyNotFound,
+    AuthFailed,
+    ConnectFailed,
+    Timeout,
+    ValidationFailed,
+    Cancelled,
+    Other,
+}
+
+impl BatchErrorKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            BatchErrorKind::KeyMissing => "key_missing",
+            BatchErrorKind::KeyNotFound => "key_not_found",
+            BatchErrorKind::AuthFailed => "auth_failed",
+            BatchErrorKind::ConnectFailed => "connect_failed",
+            BatchErrorKind::Timeout => "timeout",
+            BatchErrorKind::ValidationFailed => "validation_failed",
+            BatchErrorKind::Cancelled => "cancelled",
+            BatchErrorKind::Other => "other",
+        }
+    }
+}
+
+/// Классифицирует текст ошибки по тем же маркерам, что уже используются для
+/// построения человекочитаемого `improved_error` при ошибках подключения
+fn classify_batch_error(error_msg: &str) -> BatchErrorKind {
+    if error_msg.contains("Выполнение отменено") || error_msg.contains("прервано новым запуском") {
+        BatchErrorKind::Cancelled
+    } else if error_msg.contains("Ошибка валидации команды") {
+        BatchErrorKind::ValidationFailed
+    } else if error_msg.contains("Key path is required") || error_msg.contains("путь к ключу не указан") {
+        BatchErrorKind::KeyMissing
+    } else if error_msg.contains("Key file not found") || error_msg.contains("not found") || error_msg.contains("файл ключа не найден") {
+        BatchErrorKind::KeyNotFound
+    } else if error_msg.contains("Authentication failed") || error_msg.contains("Permission denied") || error_msg.contains("аутентификация не удалась") {
+        BatchErrorKind::AuthFailed
+    } else if error_msg.contains("Connection failed") || error_msg.contains("Failed to connect") || error_msg.contains("не удалось установить соединение") {
+        BatchErrorKind::ConnectFailed
+    } else if error_msg.contains("timeout") || error_msg.contains("Timeout") || error_msg.contains("превышено время ожидания") {
+        BatchErrorKind::Timeout
+    } else {
+        BatchErrorKind::Other
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BatchCommandResult {
     pub result: Option<ssh::SshCommandResult>,
     pub error: Option<String>,
     pub host: String,
+    #[serde(default)]
+    pub error_kind: Option<BatchErrorKind>,
+}
+
+impl BatchCommandResult {
+    fn success(host: String, result: ssh::SshCommandResult) -> Self {
+        Self { result: Some(result), error: None, host, error_kind: None }
+    }
+
+    fn failure(host: String, error: String) -> Self {
+        let error_kind = Some(classify_batch_error(&error));
+        Self { result: None, error: Some(error), host, error_kind }
+    }
 }
 
 // Счетчик ID выполнения для предотвращения смешивания результатов
@@ -278,6 +438,8 @@ pub async fn execute_batch_commands(
     request: BatchCommandRequest,
     pool: State<'_, SshPool>,
     cancellation_token: State<'_, CancellationToken>,
+    host_log_buffers: State<'_, HostLogBuffers>,
+    retry_schedules: State<'_, RetrySchedules>,
     window: Window,
 ) -> Result<Vec<BatchCommandResult>, String> {
     // Генерируем уникальный ID для этого выполнения
@@ -365,9 +527,14 @@ pub async fn execute_batch_commands(
     let pool_clone = pool.clone();
     let total_hosts = request.hosts.len();
     let command = request.command.clone();
+    let skip_validation = request.skip_validation;
     let config_template = request.config_template.clone();
     let cancellation_token_clone = cancellation_token.inner().clone();
-    
+    let streaming = request.streaming;
+    let host_log_buffers_clone = host_log_buffers.inner().clone();
+    let retry_schedules_clone = retry_schedules.inner().clone();
+    let streaming_window = window.clone();
+
     // Сохраняем оригинальный список хостов для повторных попыток
     let original_hosts = request.hosts.clone();
 
@@ -451,22 +618,14 @@ pub async fn execute_batch_commands(
                 let current_id = CURRENT_EXECUTION_ID.load(std::sync::atomic::Ordering::SeqCst);
                 if worker_execution_id != current_id {
                     // Это устаревшее выполнение, возвращаем отмену без SSH-подключения
-                    let batch_result = BatchCommandResult {
-                        result: None,
-                        error: Some("Выполнение прервано новым запуском".to_string()),
-                        host: host_ip.clone(),
-                    };
+                    let batch_result = BatchCommandResult::failure(host_ip.clone(), "Выполнение прервано новым запуском".to_string());
                     let _ = tx.send(batch_result.clone());
                     return batch_result;
                 }
-                
+
                 // Проверяем отмену перед обработкой каждого хоста
                 if cancellation_token_clone.is_cancelled() {
-                    let batch_result = BatchCommandResult {
-                        result: None,
-                        error: Some("Выполнение отменено".to_string()),
-                        host: host_ip.clone(),
-                    };
+                    let batch_result = BatchCommandResult::failure(host_ip.clone(), "Выполнение отменено".to_string());
                     let _ = tx.send(batch_result.clone());
                     return batch_result;
                 }
@@ -476,17 +635,21 @@ pub async fn execute_batch_commands(
                         let cancel_check = cancellation_token_clone.clone();
                         match pool_ref.get_or_create_cancellable(config.clone(), move || cancel_check.is_cancelled()) {
                     Ok(connection) => {
-                        log::debug!("[Batch Execute] Подключение к {} установлено", host_ip);
+                        log::debug!("[Batch Execute] Подключение к {} установлено (семейство ОС: {:?})", host_ip, connection.family());
                         // Проверяем отмену перед выполнением команды
                         if cancellation_token_clone.is_cancelled() {
                             log::info!("[Batch Execute] Выполнение отменено для {}", host_ip);
-                            return BatchCommandResult {
-                                result: None,
-                                error: Some("Выполнение отменено".to_string()),
-                                host: host_ip,
-                            };
+                            return BatchCommandResult::failure(host_ip, "Выполнение отменено".to_string());
                         }
-                        
+
+                        // Повторная валидация с учетом реального семейства ОС хоста
+                        // (первичная проверка перед подключением использовала Unix по умолчанию)
+                        if let Err(e) = crate::command_validation::validate_command_for_family(&cmd, skip_validation, connection.family()) {
+                            log::warn!("[Batch Execute] Команда отклонена для {} после определения семейства ОС: {}", host_ip, e);
+                            crate::command_audit::record_command_execution(&host_ip, &cmd, "rejected", None);
+                            return BatchCommandResult::failure(host_ip, format!("Ошибка валидации команды: {}", e));
+                        }
+
                         // Логируем выполнение команды на конкретном хосте (санитизированная версия)
                         let sanitized_cmd = crate::command_validation::sanitize_command_for_logging(&cmd);
                         let cmd_preview = if sanitized_cmd.len() > 80 {
@@ -502,8 +665,61 @@ pub async fn execute_batch_commands(
                             None,
                         );
                         
-                        match connection.execute_command(&cmd) {
+                        let exec_result = if streaming {
+                            let seq_counter = std::sync::atomic::AtomicU64::new(0);
+                            let mut line_acc = String::new();
+                            let emit_execution_id = execution_id;
+                            let streaming_window_cb = streaming_window.clone();
+                            let host_for_cb = host_ip.clone();
+                            let buffers_for_cb = host_log_buffers_clone.clone();
+                            let cancel_check_for_stream = cancellation_token_clone.clone();
+                            connection.execute_command_streaming(
+                                &cmd,
+                                true,
+                                move |chunk| {
+                                    if chunk.stream != ssh::StreamSource::Stdout {
+                                        return;
+                                    }
+                                    line_acc.push_str(&String::from_utf8_lossy(&chunk.bytes));
+                                    while let Some(pos) = line_acc.find('\n') {
+                                    let line: String = line_acc.drain(..=pos).collect::<String>()
+                                        .trim_end_matches('\n')
+                                        .to_string();
+                                    let seq = seq_counter.fetch_add(1, Ordering::SeqCst);
+                                    {
+                                        let mut buffers = buffers_for_cb.lock();
+                                        let buffer = buffers
+                                            .entry(host_for_cb.clone())
+                                            .or_insert_with(|| crate::log_buffer::LogBuffer::new(HOST_LOG_BUFFER_CAPACITY));
+                                        buffer.push_line(line.clone());
+                                    }
+                                    let _ = streaming_window_cb.emit(
+                                        "batch-output",
+                                        BatchOutputEvent {
+                                            execution_id: emit_execution_id,
+                                            host: host_for_cb.clone(),
+                                            seq,
+                                            chunk: line,
+                                        },
+                                    );
+                                }
+                            },
+                                move || cancel_check_for_stream.is_cancelled(),
+                            )
+                        } else {
+                            connection.execute_command(&cmd)
+                        };
+
+                        let batch_validation_outcome = if skip_validation { "allowed_unvalidated" } else { "allowed" };
+
+                        match exec_result {
                             Ok(result) => {
+                                crate::command_audit::record_command_execution(
+                                    &host_ip,
+                                    &cmd,
+                                    batch_validation_outcome,
+                                    Some(result.exit_status),
+                                );
                                 // Логируем успешное выполнение
                                 if result.exit_status == 0 {
                                     log::info!("[Batch Execute] Команда успешно выполнена на {} (код выхода: {})", host_ip, result.exit_status);
@@ -522,16 +738,29 @@ pub async fn execute_batch_commands(
                                         None,
                                     );
                                 }
-                                    let batch_result = BatchCommandResult {
-                                        result: Some(result),
-                                        error: None,
-                                        host: host_ip.clone(),
-                                    };
+
+                                let mut result = result;
+                                if streaming {
+                                    // Строки уже были переданы в UI вживую через execute_command_streaming;
+                                    // здесь лишь подрезаем итоговый stdout до содержимого кольцевого буфера хоста
+                                    let buffers = host_log_buffers_clone.lock();
+                                    if let Some(buffer) = buffers.get(&host_ip) {
+                                        result.stdout = buffer.lines().join("\n");
+                                    }
+                                }
+
+                                    let batch_result = BatchCommandResult::success(host_ip.clone(), result);
                                     // Отправляем результат через канал
                                     let _ = tx.send(batch_result.clone());
                                     batch_result
                                 },
                             Err(e) => {
+                                crate::command_audit::record_command_execution(
+                                    &host_ip,
+                                    &cmd,
+                                    batch_validation_outcome,
+                                    None,
+                                );
                                 // Если отмена произошла во время выполнения, возвращаем соответствующее сообщение
                                 if cancellation_token_clone.is_cancelled() {
                                     log::info!("[Batch Execute] Выполнение команды отменено на {}", host_ip);
@@ -541,11 +770,7 @@ pub async fn execute_batch_commands(
                                         &format!("Выполнение команды отменено на {}", host_ip),
                                         None,
                                     );
-                                    let batch_result = BatchCommandResult {
-                                        result: None,
-                                        error: Some("Выполнение отменено".to_string()),
-                                        host: host_ip.clone(),
-                                    };
+                                    let batch_result = BatchCommandResult::failure(host_ip.clone(), "Выполнение отменено".to_string());
                                     let _ = tx.send(batch_result.clone());
                                     batch_result
                                 } else {
@@ -556,11 +781,7 @@ pub async fn execute_batch_commands(
                                         &format!("Ошибка выполнения команды на {}: {}", host_ip, e),
                                         None,
                                     );
-                                    let batch_result = BatchCommandResult {
-                                        result: None,
-                                        error: Some(format!("{}", e)),
-                                        host: host_ip.clone(),
-                                    };
+                                    let batch_result = BatchCommandResult::failure(host_ip.clone(), format!("{}", e));
                                     let _ = tx.send(batch_result.clone());
                                     batch_result
                                 }
@@ -570,11 +791,7 @@ pub async fn execute_batch_commands(
                     Err(e) => {
                         log::error!("[Batch Execute] Ошибка подключения к {}: {}", host_ip, e);
                         if cancellation_token_clone.is_cancelled() {
-                            let batch_result = BatchCommandResult {
-                                result: None,
-                                error: Some("Выполнение отменено".to_string()),
-                                host: host_ip.clone(),
-                            };
+                            let batch_result = BatchCommandResult::failure(host_ip.clone(), "Выполнение отменено".to_string());
                             let _ = tx.send(batch_result.clone());
                             batch_result
                         } else {
@@ -601,11 +818,7 @@ pub async fn execute_batch_commands(
                                 None,
                             );
                             
-                            let batch_result = BatchCommandResult {
-                                result: None,
-                                error: Some(improved_error),
-                                host: host_ip.clone(),
-                            };
+                            let batch_result = BatchCommandResult::failure(host_ip.clone(), improved_error);
                             let _ = tx.send(batch_result.clone());
                             batch_result
                         }
@@ -646,35 +859,88 @@ pub async fn execute_batch_commands(
         return Ok(results);
     }
 
-    // Если включен режим повторных попыток, повторяем для неудачных хостов
+    // Если включен режим повторных попыток, повторяем для неудачных хостов с
+    // экспоненциальной задержкой и джиттером по каждому хосту отдельно -
+    // хосты с частыми ошибками отступают дальше друг от друга, а не ждут все разом
+    // по одному и тому же фиксированному интервалу
     if request.retry_failed_hosts {
-        let mut retry_count = 0;
-        let max_retries = 10; // Максимум 10 попыток для предотвращения бесконечного цикла
-        
+        struct HostRetryState {
+            error_count: u32,
+            last_try: chrono::DateTime<Utc>,
+            next_try: chrono::DateTime<Utc>,
+        }
+
+        let mut schedule: std::collections::HashMap<String, HostRetryState> = results
+            .iter()
+            .filter(|r| r.result.is_none())
+            .map(|r| {
+                let now = Utc::now();
+                (
+                    r.host.clone(),
+                    HostRetryState {
+                        error_count: 0,
+                        last_try: now,
+                        next_try: now,
+                    },
+                )
+            })
+            .collect();
+
+        let mut total_retries = 0u32;
+        let max_retries_per_host = 10; // Максимум 10 попыток на хост для предотвращения бесконечного цикла
+
+        let publish_schedule = |schedule: &std::collections::HashMap<String, HostRetryState>| {
+            let snapshot: Vec<RetryInfo> = schedule
+                .iter()
+                .map(|(host, state)| RetryInfo {
+                    host: host.clone(),
+                    error_count: state.error_count,
+                    last_try: state.last_try.to_rfc3339(),
+                    next_try: state.next_try.to_rfc3339(),
+                })
+                .collect();
+            retry_schedules_clone.lock().insert(execution_id, snapshot);
+        };
+        publish_schedule(&schedule);
+
         loop {
-            // Проверяем отмену перед каждой повторной попыткой
             if cancellation_token_clone.is_cancelled() {
                 break;
             }
-            
-            // Собираем список неудачных хостов
-            let failed_hosts: Vec<_> = results
+
+            // Удаляем из расписания хосты, которые уже выполнились успешно
+            schedule.retain(|host, _| {
+                results
+                    .iter()
+                    .find(|r| &r.host == host)
+                    .map(|r| r.result.is_none())
+                    .unwrap_or(false)
+            });
+
+            if schedule.is_empty() {
+                break;
+            }
+
+            // Хосты, исчерпавшие лимит попыток, больше не планируются
+            schedule.retain(|_, state| state.error_count < max_retries_per_host);
+            if schedule.is_empty() {
+                break;
+            }
+
+            let now = Utc::now();
+            let due_hosts: Vec<_> = original_hosts
                 .iter()
-                .filter(|r| r.result.is_none())
-                .filter_map(|r| {
-                    // Находим оригинальный хост из original_hosts
-                    original_hosts.iter().find(|h| h.ip == r.host).cloned()
-                })
+                .filter(|h| schedule.get(&h.ip).map(|s| s.next_try <= now).unwrap_or(false))
+                .cloned()
                 .collect();
-            
-            // Если нет неудачных хостов или достигнут лимит попыток, выходим
-            if failed_hosts.is_empty() || retry_count >= max_retries {
-                break;
+
+            if due_hosts.is_empty() {
+                // Ждем совсем немного и пересчитываем, чья очередь подошла - так расписание
+                // остается отзывчивым к отмене и не просыпает хосты одновременно
+                std::thread::sleep(std::time::Duration::from_millis(200));
+                continue;
             }
-            
-            retry_count += 1;
-            
-            // Логируем начало повторной попытки с информацией о команде
+
             let cmd_preview = if command.len() > 80 {
                 format!("{}...", &command[..80])
             } else {
@@ -683,27 +949,11 @@ pub async fn execute_batch_commands(
             audit::log_action(
                 "INFO",
                 "batch_retry",
-                &format!("Повторная попытка #{} для {} хостов. Команда: {}", retry_count, failed_hosts.len(), cmd_preview),
+                &format!("Повторная попытка для {} хостов. Команда: {}", due_hosts.len(), cmd_preview),
                 None,
             );
-            
-            // Ждем указанный интервал перед повторной попыткой с проверкой отмены
-            let sleep_duration = std::time::Duration::from_secs(request.retry_interval);
-            let sleep_start = std::time::Instant::now();
-            while sleep_start.elapsed() < sleep_duration {
-                if cancellation_token_clone.is_cancelled() {
-                    break;
-                }
-                std::thread::sleep(std::time::Duration::from_millis(100));
-            }
-            
-            // Проверяем отмену после ожидания
-            if cancellation_token_clone.is_cancelled() {
-                break;
-            }
-            
-            // Подготавливаем конфигурации для повторной попытки
-            let retry_hosts_with_configs: Vec<_> = failed_hosts
+
+            let retry_hosts_with_configs: Vec<_> = due_hosts
                 .iter()
                 .map(|host| {
                     let mut config = config_template.clone();
@@ -714,8 +964,7 @@ pub async fn execute_batch_commands(
                     let host_ip = host.ip.clone();
                     let cmd = command.clone();
                     let pool_ref = pool_clone.clone();
-                    
-                    // Логируем повторную попытку для каждого хоста
+
                     let cmd_preview = if cmd.len() > 80 {
                         format!("{}...", &cmd[..80])
                     } else {
@@ -727,38 +976,46 @@ pub async fn execute_batch_commands(
                         &format!("Повторная попытка выполнения команды на {}: {}", host_ip, cmd_preview),
                         None,
                     );
-                    
+
                     (host_ip, config, cmd, pool_ref)
                 })
                 .collect();
-            
-            // Выполняем повторную попытку
+
+            total_retries += 1;
+            for state in schedule.values_mut() {
+                state.last_try = Utc::now();
+            }
+
             let retry_results: Vec<BatchCommandResult> = thread_pool.install(|| {
                 retry_hosts_with_configs
                     .into_par_iter()
                     .map(|(host_ip, config, cmd, pool_ref)| {
-                        // Проверяем отмену перед обработкой каждого хоста
                         if cancellation_token_clone.is_cancelled() {
-                            return BatchCommandResult {
-                                result: None,
-                                error: Some("Выполнение отменено".to_string()),
-                                host: host_ip,
-                            };
+                            return BatchCommandResult::failure(host_ip, "Выполнение отменено".to_string());
                         }
-                        
+
                         match pool_ref.get_or_create(config.clone()) {
                             Ok(connection) => {
-                                // Проверяем отмену перед выполнением команды
                                 if cancellation_token_clone.is_cancelled() {
-                                    return BatchCommandResult {
-                                        result: None,
-                                        error: Some("Выполнение отменено".to_string()),
-                                        host: host_ip,
-                                    };
+                                    return BatchCommandResult::failure(host_ip, "Выполнение отменено".to_string());
                                 }
-                                
+
+                                // Повторная валидация с учетом реального семейства ОС хоста
+                                if let Err(e) = crate::command_validation::validate_command_for_family(&cmd, skip_validation, connection.family()) {
+                                    crate::command_audit::record_command_execution(&host_ip, &cmd, "rejected", None);
+                                    return BatchCommandResult::failure(host_ip, format!("Ошибка валидации команды: {}", e));
+                                }
+
+                                let retry_validation_outcome = if skip_validation { "allowed_unvalidated" } else { "allowed" };
+
                                 match connection.execute_command(&cmd) {
                                     Ok(result) => {
+                                        crate::command_audit::record_command_execution(
+                                            &host_ip,
+                                            &cmd,
+                                            retry_validation_outcome,
+                                            Some(result.exit_status),
+                                        );
                                         if result.exit_status == 0 {
                                             audit::log_action(
                                                 "INFO",
@@ -774,13 +1031,15 @@ pub async fn execute_batch_commands(
                                                 None,
                                             );
                                         }
-                                        BatchCommandResult {
-                                            result: Some(result),
-                                            error: None,
-                                            host: host_ip,
-                                        }
+                                        BatchCommandResult::success(host_ip, result)
                                     },
                                     Err(e) => {
+                                        crate::command_audit::record_command_execution(
+                                            &host_ip,
+                                            &cmd,
+                                            retry_validation_outcome,
+                                            None,
+                                        );
                                         if cancellation_token_clone.is_cancelled() {
                                             audit::log_action(
                                                 "INFO",
@@ -788,11 +1047,7 @@ pub async fn execute_batch_commands(
                                                 &format!("Повторная попытка отменена на {}", host_ip),
                                                 None,
                                             );
-                                            BatchCommandResult {
-                                                result: None,
-                                                error: Some("Выполнение отменено".to_string()),
-                                                host: host_ip,
-                                            }
+                                            BatchCommandResult::failure(host_ip, "Выполнение отменено".to_string())
                                         } else {
                                             audit::log_action(
                                                 "ERROR",
@@ -800,54 +1055,60 @@ pub async fn execute_batch_commands(
                                                 &format!("Повторная попытка на {} завершилась ошибкой: {}", host_ip, e),
                                                 None,
                                             );
-                                            BatchCommandResult {
-                                                result: None,
-                                                error: Some(format!("{}", e)),
-                                                host: host_ip,
-                                            }
+                                            BatchCommandResult::failure(host_ip, format!("{}", e))
                                         }
                                     },
                                 }
                             },
                             Err(e) => {
                                 if cancellation_token_clone.is_cancelled() {
-                                    BatchCommandResult {
-                                        result: None,
-                                        error: Some("Выполнение отменено".to_string()),
-                                        host: host_ip,
-                                    }
+                                    BatchCommandResult::failure(host_ip, "Выполнение отменено".to_string())
                                 } else {
-                                    BatchCommandResult {
-                                        result: None,
-                                        error: Some(format!("Connection failed: {}", e)),
-                                        host: host_ip,
-                                    }
+                                    BatchCommandResult::failure(host_ip, format!("Connection failed: {}", e))
                                 }
                             },
                         }
                     })
                     .collect()
             });
-            
-            // Обновляем результаты: заменяем неудачные результаты на новые
+
+            // Обновляем результаты и расписание по каждому хосту индивидуально
             for retry_result in retry_results {
                 if let Some(existing_result) = results.iter_mut().find(|r| r.host == retry_result.host) {
-                    // Если повторная попытка успешна, обновляем результат
                     if retry_result.result.is_some() {
-                        *existing_result = retry_result;
+                        *existing_result = retry_result.clone();
+                        schedule.remove(&retry_result.host);
                     } else {
-                        // Если снова неудача, обновляем ошибку (может быть другая)
-                        existing_result.error = retry_result.error;
+                        existing_result.error = retry_result.error.clone();
+                        if let Some(state) = schedule.get_mut(&retry_result.host) {
+                            state.error_count += 1;
+                            let backoff_secs = request
+                                .retry_interval
+                                .saturating_mul(1u64 << state.error_count.min(31))
+                                .min(MAX_RETRY_BACKOFF_SECS);
+                            let jitter_secs = rand::thread_rng().gen_range(0..=request.retry_interval.max(1));
+                            state.next_try = Utc::now()
+                                + chrono::TimeDelta::try_seconds((backoff_secs + jitter_secs) as i64)
+                                    .unwrap_or(chrono::TimeDelta::zero());
+                        }
                     }
                 }
             }
+
+            publish_schedule(&schedule);
+
+            if cancellation_token_clone.is_cancelled() {
+                break;
+            }
         }
-        
-        if retry_count > 0 {
+
+        retry_schedules_clone.lock().remove(&execution_id);
+
+        if total_retries > 0 {
             audit::log_action(
                 "INFO",
                 "batch_retry_complete",
-                &format!("Завершено {} повторных попыток", retry_count),
+                &format!("Завершено {} раундов повторных попыток", total_retries),
                 None,
             );
         }
@@ -904,15 +1165,14 @@ pub async fn export_to_excel(
         "html" | "htm" => {
             crate::excel_export::export_to_excel_html(request)
         }
+        "json" => {
+            crate::excel_export::export_to_excel_json(request)
+        }
+        "jsonl" => {
+            crate::excel_export::export_to_excel_jsonl(request)
+        }
         "xlsx" | "xls" => {
-            // Для Excel используем JSON как промежуточный формат
-            // Фронтенд обработает его через библиотеку xlsx
-            let export_data = serde_json::to_string_pretty(&request.results)
-                .map_err(|e| format!("Failed to serialize data: {}", e))?;
-            
-            std::fs::write(&request.file_path, export_data)
-                .map_err(|e| format!("Failed to write file: {}", e))?;
-            Ok(())
+            crate::excel_export::export_to_excel_xlsx(request)
         }
         _ => {
             // По умолчанию CSV
@@ -932,6 +1192,43 @@ pub async fn clear_audit_logs() -> Result<(), String> {
     audit::clear_audit_logs()
 }
 
+/// Запрашивает журнал аудита из индексированного SQLite-хранилища с фильтрацией по времени,
+/// уровню, действию и хосту, с поддержкой пагинации
+#[tauri::command]
+pub async fn query_audit_logs(filter: audit::AuditLogFilter) -> Result<Vec<audit::AuditLog>, String> {
+    audit::query_audit_logs(&filter)
+}
+
+/// Экспортирует еще не выгруженные записи аудита в TimescaleDB-совместимую гипертаблицу Postgres
+#[tauri::command]
+pub async fn export_audit_timeseries(postgres_url: String, hypertable: Option<String>) -> Result<usize, String> {
+    let table = hypertable.unwrap_or_else(|| "audit_log_timeseries".to_string());
+    let exported = audit::export_audit_to_timeseries(&postgres_url, &table)?;
+    audit::log_action(
+        "INFO",
+        "export_audit_timeseries",
+        &format!("Экспортировано {} записей аудита в таблицу '{}'", exported, table),
+        None,
+    );
+    Ok(exported)
+}
+
+/// Подписывает только что открытое окно на живой поток аудита (`audit://entry`) и
+/// возвращает "повтор" последних записей из кольцевого буфера, чтобы окно сразу
+/// увидело недавнюю историю, а не ждало первого нового события
+#[tauri::command]
+pub async fn subscribe_audit_stream(limit: Option<usize>) -> Result<Vec<audit::AuditLog>, String> {
+    Ok(audit::replay_audit_stream(limit))
+}
+
+/// Симметричная команда для unsubscribe - сама трансляция идет через широковещательное
+/// `emit_all`, поэтому отписка не требует состояния на бэкенде, но сохраняется как
+/// парная команда для симметрии API с фронтендом
+#[tauri::command]
+pub async fn unsubscribe_audit_stream() -> Result<(), String> {
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn save_temp_file(
     content: Vec<u8>,
@@ -969,8 +1266,12 @@ pub async fn save_file(
 #[tauri::command]
 pub async fn cancel_command_execution(
     cancellation_token: State<'_, CancellationToken>,
+    shells: State<'_, ShellManager>,
+    watchers: State<'_, RemoteWatchRegistry>,
 ) -> Result<(), String> {
     cancellation_token.cancel();
+    shells.close_all();
+    watchers.stop_all();
     audit::log_action("INFO", "cancel_command", "Выполнение команды отменено пользователем", None);
     Ok(())
 }
@@ -983,7 +1284,11 @@ pub async fn update_audit_settings(
     max_log_file_size: u64,
     log_format: String,
     enable_audit: bool,
+    remote_sink: Option<audit::RemoteSinkConfig>,
+    max_archives: Option<u32>,
+    max_archive_total_size_mb: Option<u64>,
 ) -> Result<(), String> {
+    let defaults = audit::AuditSettings::default();
     let settings = audit::AuditSettings {
         log_level,
         retention_days,
@@ -991,6 +1296,9 @@ pub async fn update_audit_settings(
         max_log_file_size,
         log_format,
         enable_audit,
+        remote_sink,
+        max_archives: max_archives.unwrap_or(defaults.max_archives),
+        max_archive_total_size_mb: max_archive_total_size_mb.unwrap_or(defaults.max_archive_total_size_mb),
     };
     audit::update_audit_settings(settings);
     Ok(())
@@ -1008,8 +1316,411 @@ pub async fn verify_settings_password(password: String, hash: String) -> Result<
         .map_err(|e| format!("Ошибка проверки пароля: {}", e))
 }
 
+/// Генерирует новую пару SSH-ключей (RSA 4096 или Ed25519) и сохраняет ее в
+/// зашифрованном хранилище ключей. Каждое изменение состава ключей проходит через
+/// audit::log_action с отдельной категорией действия, чтобы жизненный цикл ключей был
+/// прослеживаем независимо от обычных действий с хостами.
+#[tauri::command]
+pub async fn create_ssh_key(name: String, key_type: String, passphrase: Option<String>) -> Result<crate::keys::SshKeyInfo, String> {
+    let info = crate::keys::create_ssh_key(name, key_type, passphrase)?;
+    audit::log_action("INFO", "ssh_key_create", &format!("Создан SSH-ключ '{}' ({})", info.name, info.key_type), None);
+    Ok(info)
+}
+
+#[tauri::command]
+pub async fn list_ssh_keys() -> Result<Vec<crate::keys::SshKeyInfo>, String> {
+    crate::keys::list_ssh_keys()
+}
+
+#[tauri::command]
+pub async fn delete_ssh_key(key_id: String) -> Result<(), String> {
+    crate::keys::delete_ssh_key(&key_id)?;
+    audit::log_action("INFO", "ssh_key_delete", &format!("Удален SSH-ключ {}", key_id), None);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn import_ssh_key(name: String, private_key_pem: String, passphrase: Option<String>) -> Result<crate::keys::SshKeyInfo, String> {
+    let info = crate::keys::import_ssh_key(name, private_key_pem, passphrase)?;
+    audit::log_action("INFO", "ssh_key_import", &format!("Импортирован SSH-ключ '{}' ({})", info.name, info.key_type), None);
+    Ok(info)
+}
+
+#[tauri::command]
+pub async fn reset_key_passphrase(key_id: String, old_passphrase: Option<String>, new_passphrase: Option<String>) -> Result<(), String> {
+    crate::keys::reset_key_passphrase(&key_id, old_passphrase, new_passphrase)?;
+    audit::log_action("INFO", "ssh_key_reset_passphrase", &format!("Изменен passphrase SSH-ключа {}", key_id), None);
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn set_close_to_tray(enabled: bool) -> Result<(), String> {
     crate::set_close_to_tray_setting(enabled);
     Ok(())
 }
+
+pub type ShellManager = std::sync::Arc<crate::shell::ShellSessionManager>;
+
+#[tauri::command]
+pub async fn open_shell_session(
+    config: ssh::SshConfig,
+    cols: Option<u32>,
+    rows: Option<u32>,
+    pool: State<'_, SshPool>,
+    shells: State<'_, ShellManager>,
+    cancellation_token: State<'_, CancellationToken>,
+    window: Window,
+) -> Result<String, String> {
+    let size = crate::shell::PtySize {
+        cols: cols.unwrap_or(80),
+        rows: rows.unwrap_or(24),
+    };
+
+    audit::log_action("INFO", "open_shell", &format!("Открытие интерактивной сессии на {}", config.host), None);
+
+    let connection = pool.get_or_create(config).map_err(|e| e.to_string())?;
+    let cancel_check = cancellation_token.inner().clone();
+    shells
+        .open_session(connection, size, window, move || cancel_check.is_cancelled())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn write_shell_input(
+    session_id: String,
+    data: Vec<u8>,
+    shells: State<'_, ShellManager>,
+) -> Result<(), String> {
+    shells.write_input(&session_id, &data).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn resize_shell(
+    session_id: String,
+    cols: u32,
+    rows: u32,
+    shells: State<'_, ShellManager>,
+) -> Result<(), String> {
+    shells.resize(&session_id, cols, rows).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchResultSummary {
+    pub total_hosts: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+    pub elapsed_seconds: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct BatchExportPayload {
+    summary: BatchResultSummary,
+    results: Vec<BatchCommandResult>,
+}
+
+fn summarize_batch_results(results: &[BatchCommandResult], elapsed_seconds: Option<f64>) -> BatchResultSummary {
+    let total_hosts = results.len();
+    let succeeded = results.iter().filter(|r| r.result.as_ref().map(|res| res.exit_status == 0).unwrap_or(false)).count();
+    let cancelled = results.iter().filter(|r| r.error.as_deref() == Some("Выполнение отменено")).count();
+    let failed = total_hosts.saturating_sub(succeeded).saturating_sub(cancelled);
+
+    BatchResultSummary {
+        total_hosts,
+        succeeded,
+        failed,
+        cancelled,
+        elapsed_seconds,
+    }
+}
+
+/// Экспортирует накопленные результаты пакетного выполнения в JSON или CSV
+/// для передачи в смежные инструменты/CI, вместе с самоописательной сводкой
+#[tauri::command]
+pub async fn export_batch_results(
+    results: Vec<BatchCommandResult>,
+    format: String,
+    file_path: Option<String>,
+    elapsed_seconds: Option<f64>,
+) -> Result<String, String> {
+    let summary = summarize_batch_results(&results, elapsed_seconds);
+
+    let content = match format.to_lowercase().as_str() {
+        "json" => {
+            let payload = BatchExportPayload { summary: summary.clone(), results: results.clone() };
+            serde_json::to_string_pretty(&payload)
+                .map_err(|e| format!("Failed to serialize batch results to JSON: {}", e))?
+        }
+        "jsonl" => {
+            let mut lines = Vec::with_capacity(results.len());
+            for r in &results {
+                lines.push(
+                    serde_json::to_string(r)
+                        .map_err(|e| format!("Failed to serialize batch result to JSON: {}", e))?,
+                );
+            }
+            lines.join("\n")
+        }
+        "csv" => {
+            let mut wtr = csv::WriterBuilder::new().from_writer(vec![]);
+            wtr.write_record(["host", "exit_status", "stdout", "stderr", "error", "error_kind"])
+                .map_err(|e| format!("Failed to write CSV header: {}", e))?;
+            for r in &results {
+                let (exit_status, stdout, stderr) = match &r.result {
+                    Some(res) => (res.exit_status.to_string(), res.stdout.clone(), res.stderr.clone()),
+                    None => (String::new(), String::new(), String::new()),
+                };
+                let error_kind = r.error_kind.map(|k| k.as_str()).unwrap_or("");
+                wtr.write_record([&r.host, &exit_status, &stdout, &stderr, r.error.as_deref().unwrap_or(""), error_kind])
+                    .map_err(|e| format!("Failed to write CSV record: {}", e))?;
+            }
+            let bytes = wtr.into_inner().map_err(|e| format!("Failed to finalize CSV: {}", e))?;
+            String::from_utf8(bytes).map_err(|e| format!("Invalid UTF-8 in CSV output: {}", e))?
+        }
+        other => return Err(format!("Unsupported export format: {}", other)),
+    };
+
+    if let Some(path) = file_path {
+        std::fs::write(&path, &content).map_err(|e| format!("Failed to write export file: {}", e))?;
+    }
+
+    audit::log_action(
+        "INFO",
+        "export_batch_results",
+        &format!("Экспорт результатов пакетного выполнения в формате {}: {} успешно, {} ошибок, {} отменено",
+            format, summary.succeeded, summary.failed, summary.cancelled),
+        None,
+    );
+
+    Ok(content)
+}
+
+pub type PortForwardRegistry = std::sync::Arc<crate::tunnel::PortForwardManager>;
+
+#[tauri::command]
+pub async fn start_port_forward(
+    forward: crate::tunnel::PortForwardConfig,
+    pool: State<'_, SshPool>,
+    forwards: State<'_, PortForwardRegistry>,
+    window: Window,
+) -> Result<String, String> {
+    audit::log_action(
+        "INFO",
+        "start_port_forward",
+        &format!(
+            "Запуск проброса порта {}:{} -> {}:{}",
+            forward.local_host, forward.local_port, forward.remote_host, forward.remote_port
+        ),
+        None,
+    );
+    forwards
+        .start(forward, pool.inner().clone(), window)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn stop_port_forward(
+    bind_key: String,
+    forwards: State<'_, PortForwardRegistry>,
+) -> Result<bool, String> {
+    audit::log_action("INFO", "stop_port_forward", &format!("Остановка проброса порта {}", bind_key), None);
+    Ok(forwards.stop(&bind_key))
+}
+
+#[tauri::command]
+pub async fn list_port_forwards(
+    forwards: State<'_, PortForwardRegistry>,
+) -> Result<Vec<(crate::tunnel::PortForwardConfig, crate::tunnel::TunnelStatus)>, String> {
+    Ok(forwards.list())
+}
+
+pub type RemoteWatchRegistry = std::sync::Arc<crate::watch::RemoteWatchManager>;
+
+/// Запускает наблюдение за удаленным путем на каждом из выбранных хостов, переиспользуя
+/// пул SSH-соединений. Полезно, например, для слежения за лог- или выходным файлом,
+/// который создает пакетная команда, без ожидания ее завершения
+#[tauri::command]
+pub async fn watch_remote_path(
+    path: String,
+    hosts: Vec<ssh::SshConfig>,
+    poll_interval_secs: Option<u64>,
+    pool: State<'_, SshPool>,
+    watchers: State<'_, RemoteWatchRegistry>,
+    window: Window,
+) -> Result<String, String> {
+    audit::log_action(
+        "INFO",
+        "watch_remote_path",
+        &format!("Запуск наблюдения за {} на {} хостах", path, hosts.len()),
+        None,
+    );
+    let interval = std::time::Duration::from_secs(poll_interval_secs.unwrap_or(5));
+    Ok(watchers.watch(path, hosts, interval, pool.inner().clone(), window))
+}
+
+#[tauri::command]
+pub async fn unwatch_remote_path(
+    watch_id: String,
+    watchers: State<'_, RemoteWatchRegistry>,
+) -> Result<bool, String> {
+    audit::log_action("INFO", "unwatch_remote_path", &format!("Остановка наблюдения {}", watch_id), None);
+    Ok(watchers.unwatch(&watch_id))
+}
+
+#[tauri::command]
+pub async fn get_retry_schedule(
+    execution_id: u64,
+    retry_schedules: State<'_, RetrySchedules>,
+) -> Result<Vec<RetryInfo>, String> {
+    Ok(retry_schedules.lock().get(&execution_id).cloned().unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn get_host_log_buffer(
+    host: String,
+    limit: Option<usize>,
+    host_log_buffers: State<'_, HostLogBuffers>,
+) -> Result<Vec<String>, String> {
+    let buffers = host_log_buffers.lock();
+    let lines = buffers.get(&host).map(|b| b.lines()).unwrap_or_default();
+    Ok(match limit {
+        Some(n) if n < lines.len() => lines[lines.len() - n..].to_vec(),
+        _ => lines,
+    })
+}
+
+#[tauri::command]
+pub async fn close_shell_session(
+    session_id: String,
+    shells: State<'_, ShellManager>,
+) -> Result<(), String> {
+    audit::log_action("INFO", "close_shell", &format!("Закрытие интерактивной сессии {}", session_id), None);
+    shells.close_session(&session_id).map_err(|e| e.to_string())
+}
+
+/// Разблокирует зашифрованное хранилище учетных данных мастер-паролем пользователя.
+/// Должна быть вызвана один раз за сессию приложения, прежде чем vault_save_config/
+/// vault_load_config/vault_list/vault_delete станут доступны.
+#[tauri::command]
+pub async fn vault_unlock(master_password: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let app_data_dir = app_handle
+        .path_resolver()
+        .app_data_dir()
+        .ok_or_else(|| "Не удалось определить директорию данных приложения".to_string())?;
+    crate::vault::init_vault(app_data_dir, &master_password)
+        .await
+        .map_err(|e| e.to_string())?;
+    audit::log_action("INFO", "vault_unlock", "Хранилище учетных данных разблокировано", None);
+    Ok(())
+}
+
+/// Переключает шифрование хранимых паролей (`security::encrypt_password`/`decrypt_password`)
+/// на защиту мастер-паролем пользователя вместо обычного keyfile-режима: при первом
+/// вызове оборачивает сгенерированный ключ шифрования под выведенным из пароля KEK, а
+/// при последующих - разворачивает его, если пароль совпадает. Неверный пароль к уже
+/// настроенному мастер-паролю возвращается как обычная ошибка.
+#[tauri::command]
+pub async fn unlock_encryption_with_master_password(master_password: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let app_data_dir = app_handle
+        .path_resolver()
+        .app_data_dir()
+        .ok_or_else(|| "Не удалось определить директорию данных приложения".to_string())?;
+    crate::security::unlock_with_master_password(app_data_dir, &master_password).map_err(|e| e.to_string())?;
+    audit::log_action("INFO", "encryption_unlock", "Шифрование разблокировано мастер-паролем", None);
+    Ok(())
+}
+
+/// Запускает ротацию ключа шифрования хранимых паролей: сразу переводит новые
+/// `encrypt_password` на новый ключ, оставляя прежний доступным для уже сохраненных
+/// записей. Фронтенд должен перешифровать их через `re_encrypt_all_secrets` и вызвать
+/// `finish_key_rotation`, прежде чем новый ключ будет зафиксирован на диске.
+#[tauri::command]
+pub async fn start_encryption_key_rotation() -> Result<(), String> {
+    crate::security::rotate_encryption_key().map_err(|e| e.to_string())?;
+    audit::log_action("INFO", "encryption_key_rotation_start", "Начата ротация ключа шифрования", None);
+    Ok(())
+}
+
+/// Перешифровывает все записи, запечатанные напрямую под `security::ENCRYPTION_KEY` и
+/// хранящиеся на диске (сейчас это passphrase управляемых SSH-ключей в реестре `keys.rs`
+/// - `vault.rs` в этот список не входит, так как заново заворачивает секреты под
+/// собственным ключом хранилища перед записью в БД). Должна вызываться после
+/// `start_encryption_key_rotation` и до `finish_encryption_key_rotation` - иначе эти
+/// записи останутся под ключом, который `finish_key_rotation` отбросит, и станут нечитаемы.
+/// Возвращает число фактически перешифрованных записей.
+#[tauri::command]
+pub async fn re_encrypt_all_secrets() -> Result<usize, String> {
+    let count = crate::keys::re_encrypt_all_passphrases()?;
+    audit::log_action(
+        "INFO",
+        "encryption_key_rotation_reencrypt",
+        &format!("Перешифровано записей под новым ключом: {}", count),
+        None,
+    );
+    Ok(count)
+}
+
+/// Фиксирует ротацию, начатую `start_encryption_key_rotation`, после того как все
+/// хранимые `EncryptedData` успешно перешифрованы через `re_encrypt_all_secrets` - только
+/// теперь новый ключ перезаписывает `encryption.key`/конверт мастер-пароля на диске.
+#[tauri::command]
+pub async fn finish_encryption_key_rotation(master_password: Option<String>, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let app_data_dir = app_handle.path_resolver().app_data_dir();
+    crate::security::finish_key_rotation(app_data_dir, master_password.as_deref()).map_err(|e| e.to_string())?;
+    audit::log_action("INFO", "encryption_key_rotation_finish", "Ротация ключа шифрования завершена", None);
+    Ok(())
+}
+
+/// Экспортирует текущий ключ шифрования в виде recovery-кода (бумажного ключа), который
+/// пользователь может сохранить отдельно и позже использовать для восстановления доступа
+/// к зашифрованным паролям на новой машине через `import_recovery_code`.
+#[tauri::command]
+pub async fn export_recovery_code() -> Result<String, String> {
+    let code = crate::security::export_recovery_code()?;
+    audit::log_action("INFO", "encryption_recovery_export", "Экспортирован recovery-код ключа шифрования", None);
+    Ok(code)
+}
+
+/// Восстанавливает ключ шифрования из recovery-кода, выданного `export_recovery_code`, и
+/// перезаписывает им `encryption.key` (либо конверт мастер-пароля, если он настроен -
+/// тогда `master_password` обязателен). Неверный или поврежденный код отклоняется до того,
+/// как он попадет в использование.
+#[tauri::command]
+pub async fn import_recovery_code(code: String, master_password: Option<String>, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let app_data_dir = app_handle.path_resolver().app_data_dir();
+    crate::security::import_recovery_code(&code, app_data_dir, master_password.as_deref()).map_err(|e| e.to_string())?;
+    audit::log_action("INFO", "encryption_recovery_import", "Ключ шифрования восстановлен из recovery-кода", None);
+    Ok(())
+}
+
+/// Проверяет целостность защищенного от подделки журнала выполненных команд,
+/// пройдя по всей HMAC-цепочке. Возвращает индекс первой нарушенной записи,
+/// либо `None`, если цепочка цела.
+#[tauri::command]
+pub async fn verify_command_audit_log() -> Result<Option<usize>, String> {
+    crate::command_audit::verify_audit_log().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn vault_save_config(config: ssh::SshConfig, label: String) -> Result<String, String> {
+    let id = crate::vault::save_config(&config, &label).await.map_err(|e| e.to_string())?;
+    audit::log_action("INFO", "vault_save", &format!("Сохранено подключение '{}' ({}@{})", label, config.username, config.host), None);
+    Ok(id)
+}
+
+#[tauri::command]
+pub async fn vault_load_config(id: String) -> Result<ssh::SshConfig, String> {
+    crate::vault::load_config(&id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn vault_list() -> Result<Vec<crate::vault::VaultEntry>, String> {
+    crate::vault::list().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn vault_delete(id: String) -> Result<(), String> {
+    crate::vault::delete(&id).await.map_err(|e| e.to_string())?;
+    audit::log_action("INFO", "vault_delete", &format!("Удалено сохраненное подключение {}", id), None);
+    Ok(())
+}
@@ -17,6 +17,9 @@ pub enum AppError {
     
     #[error("Ошибка подключения: {0}")]
     ConnectionError(String),
+
+    #[error("Ошибка хранилища учетных данных: {0}")]
+    VaultError(String),
 }
 
 impl Serialize for AppError {
@@ -120,8 +120,8 @@ pub fn export_to_excel_csv(request: ExcelExportRequest) -> Result<(), String> {
                 "exitStatus" | "exit_status" => result.exit_status.to_string(),
                 "stdout" => result.stdout.clone(),
                 "stderr" => result.stderr.clone(),
-                "timestamp" => "".to_string(), // TODO: добавить timestamp если будет доступен
-                "command" => "".to_string(), // TODO: добавить command если будет доступен
+                "timestamp" => result.timestamp.map(|t| t.to_rfc3339()).unwrap_or_default(),
+                "command" => result.command.clone(),
                 _ => "".to_string(),
             }
         }).collect();
@@ -238,8 +238,8 @@ pub fn export_to_excel_html(request: ExcelExportRequest) -> Result<(), String> {
                 "exitStatus" | "exit_status" => result.exit_status.to_string(),
                 "stdout" => format!("<pre>{}</pre>", html_escape(&result.stdout)),
                 "stderr" => format!("<pre>{}</pre>", html_escape(&result.stderr)),
-                "timestamp" => "".to_string(), // TODO: добавить timestamp если будет доступен
-                "command" => "".to_string(), // TODO: добавить command если будет доступен
+                "timestamp" => html_escape(&result.timestamp.map(|t| t.to_rfc3339()).unwrap_or_default()),
+                "command" => html_escape(&result.command),
                 _ => "".to_string(),
             };
             html.push_str(&format!("            <td>{}</td>\n", cell_value));
@@ -260,6 +260,203 @@ pub fn export_to_excel_html(request: ExcelExportRequest) -> Result<(), String> {
     Ok(())
 }
 
+/// Экспортирует результаты в настоящий `.xlsx` через `rust_xlsxwriter` (а не переименованный
+/// CSV/JSON, как раньше): жирный заголовок, закрепленная первая строка, заливка статуса
+/// зеленым/красным - аналог классов `.success`/`.error` из `export_to_excel_html` - и
+/// примерный авто-подбор ширины столбцов по самому длинному значению.
+pub fn export_to_excel_xlsx(request: ExcelExportRequest) -> Result<(), String> {
+    use rust_xlsxwriter::{Format, Workbook};
+
+    // Получаем настройки столбцов (с учетом обратной совместимости)
+    let col_settings = request.column_settings.clone().unwrap_or_default();
+
+    // Определяем порядок и включенные столбцы
+    let default_order = vec![
+        "host".to_string(),
+        "vehicle_id".to_string(),
+        "status".to_string(),
+        "exit_status".to_string(),
+        "stdout".to_string(),
+        "stderr".to_string(),
+    ];
+    let column_order = if col_settings.column_order.is_empty() {
+        default_order
+    } else {
+        col_settings.column_order.clone()
+    };
+
+    // Определяем, какие столбцы включены
+    let enabled_columns: Vec<String> = column_order
+        .into_iter()
+        .filter(|col| {
+            match col.as_str() {
+                "host" => col_settings.host,
+                "vehicleId" | "vehicle_id" => col_settings.vehicle_id,
+                "status" => col_settings.status,
+                "exitStatus" | "exit_status" => col_settings.exit_status,
+                "stdout" => col_settings.stdout,
+                "stderr" => col_settings.stderr,
+                "timestamp" => col_settings.timestamp,
+                "command" => col_settings.command,
+                _ => false,
+            }
+        })
+        .collect();
+
+    let mut workbook = Workbook::new();
+    let sheet_name = request.sheet_name.as_deref().unwrap_or("Результаты");
+    let worksheet = workbook
+        .add_worksheet()
+        .set_name(sheet_name)
+        .map_err(|e| format!("Failed to set sheet name: {}", e))?;
+
+    let header_format = Format::new()
+        .set_bold()
+        .set_background_color("#4CAF50")
+        .set_font_color("#FFFFFF");
+    let success_format = Format::new().set_font_color("#008000");
+    let error_format = Format::new().set_font_color("#FF0000");
+
+    // Если заголовок включен, данные начинаются со второй строки
+    let header_row: u32 = if col_settings.include_headers { 1 } else { 0 };
+
+    if col_settings.include_headers {
+        for (col_idx, col) in enabled_columns.iter().enumerate() {
+            let header = match col.as_str() {
+                "host" => "Хост",
+                "vehicleId" | "vehicle_id" => "ID ТС",
+                "status" => "Статус",
+                "exitStatus" | "exit_status" => "Код выхода",
+                "stdout" => "Вывод",
+                "stderr" => "Ошибки",
+                "timestamp" => "Время выполнения",
+                "command" => "Команда",
+                _ => "",
+            };
+            worksheet
+                .write_string_with_format(0, col_idx as u16, header, &header_format)
+                .map_err(|e| format!("Failed to write header: {}", e))?;
+        }
+    }
+
+    // Данные
+    for (row_idx, result) in request.results.iter().enumerate() {
+        let row = header_row + row_idx as u32;
+        let status = if result.exit_status == 0 {
+            "Успешно"
+        } else {
+            "Ошибка"
+        };
+        let status_format = if result.exit_status == 0 {
+            &success_format
+        } else {
+            &error_format
+        };
+        let vehicle_id = result.vehicle_id.as_deref().unwrap_or("");
+
+        for (col_idx, col) in enabled_columns.iter().enumerate() {
+            let col_idx = col_idx as u16;
+            match col.as_str() {
+                "host" => worksheet.write_string(row, col_idx, &result.host),
+                "vehicleId" | "vehicle_id" => worksheet.write_string(row, col_idx, vehicle_id),
+                "status" => worksheet.write_string_with_format(row, col_idx, status, status_format),
+                "exitStatus" | "exit_status" => {
+                    worksheet.write_number(row, col_idx, result.exit_status as f64)
+                }
+                "stdout" => worksheet.write_string(row, col_idx, &result.stdout),
+                "stderr" => worksheet.write_string(row, col_idx, &result.stderr),
+                "timestamp" => worksheet.write_string(
+                    row,
+                    col_idx,
+                    &result.timestamp.map(|t| t.to_rfc3339()).unwrap_or_default(),
+                ),
+                "command" => worksheet.write_string(row, col_idx, &result.command),
+                _ => worksheet.write_string(row, col_idx, ""),
+            }
+            .map_err(|e| format!("Failed to write cell: {}", e))?;
+        }
+    }
+
+    // Закрепляем строку заголовка, чтобы она оставалась на месте при прокрутке
+    worksheet
+        .set_freeze_panes(header_row, 0)
+        .map_err(|e| format!("Failed to freeze header pane: {}", e))?;
+
+    // Авто-подбор ширины столбца по самому длинному значению (плюс небольшой запас)
+    for (col_idx, col) in enabled_columns.iter().enumerate() {
+        let header_len = if col_settings.include_headers {
+            match col.as_str() {
+                "host" => 4,
+                "vehicleId" | "vehicle_id" => 5,
+                "status" => 6,
+                "exitStatus" | "exit_status" => 10,
+                "stdout" => 5,
+                "stderr" => 6,
+                "timestamp" => 16,
+                "command" => 7,
+                _ => 0,
+            }
+        } else {
+            0
+        };
+
+        let max_len = request
+            .results
+            .iter()
+            .map(|result| match col.as_str() {
+                "host" => result.host.len(),
+                "vehicleId" | "vehicle_id" => result.vehicle_id.as_deref().unwrap_or("").len(),
+                "status" => "Успешно".len(),
+                "exitStatus" | "exit_status" => result.exit_status.to_string().len(),
+                "stdout" => result.stdout.lines().map(str::len).max().unwrap_or(0),
+                "stderr" => result.stderr.lines().map(str::len).max().unwrap_or(0),
+                _ => 0,
+            })
+            .max()
+            .unwrap_or(0)
+            .max(header_len);
+
+        let width = (max_len as f64 + 2.0).clamp(8.0, 80.0);
+        worksheet
+            .set_column_width(col_idx as u16, width)
+            .map_err(|e| format!("Failed to set column width: {}", e))?;
+    }
+
+    workbook
+        .save(&request.file_path)
+        .map_err(|e| format!("Failed to save xlsx file: {}", e))?;
+
+    Ok(())
+}
+
+/// Экспортирует результаты как единый JSON-массив - машинно-читаемый формат
+/// для передачи результатов в скрипты/CI без парсинга локализованного CSV/HTML
+pub fn export_to_excel_json(request: ExcelExportRequest) -> Result<(), String> {
+    let export_data = serde_json::to_string_pretty(&request.results)
+        .map_err(|e| format!("Failed to serialize data: {}", e))?;
+
+    std::fs::write(&request.file_path, export_data)
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+
+    Ok(())
+}
+
+/// Экспортирует результаты в формате JSON Lines (по одному результату на строку) -
+/// удобно для потоковой обработки больших наборов результатов построчно
+pub fn export_to_excel_jsonl(request: ExcelExportRequest) -> Result<(), String> {
+    let mut lines = Vec::with_capacity(request.results.len());
+    for result in &request.results {
+        lines.push(
+            serde_json::to_string(result).map_err(|e| format!("Failed to serialize record: {}", e))?,
+        );
+    }
+
+    std::fs::write(&request.file_path, lines.join("\n"))
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+
+    Ok(())
+}
+
 fn html_escape(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
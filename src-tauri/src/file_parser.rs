@@ -6,7 +6,6 @@ use std::sync::OnceLock;
 
 // Кэшируем регулярные выражения для производительности
 static IP_REGEX: OnceLock<Regex> = OnceLock::new();
-static PORT_REGEX: OnceLock<Regex> = OnceLock::new();
 
 fn get_ip_regex() -> &'static Regex {
     IP_REGEX.get_or_init(|| {
@@ -15,13 +14,6 @@ fn get_ip_regex() -> &'static Regex {
     })
 }
 
-fn get_port_regex() -> &'static Regex {
-    PORT_REGEX.get_or_init(|| {
-        Regex::new(r":(\d{1,5})")
-            .expect("Port regex pattern is invalid")
-    })
-}
-
 /// Проверяет, является ли строка валидным IPv4 адресом
 /// Каждый октет должен быть в диапазоне 0-255
 /// Ведущие нули не допускаются (001, 01 и т.д.)
@@ -48,15 +40,303 @@ fn is_valid_ipv4(ip: &str) -> bool {
             _ => return false,
         }
     }
-    
+
     true
 }
 
+/// Одна группа IPv6-адреса: 1-4 hex-цифры
+fn is_valid_hex_group(group: &str) -> bool {
+    !group.is_empty() && group.len() <= 4 && group.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Проверяет, является ли строка валидным IPv6 адресом (без скобок и порта).
+/// Разбивает адрес на группы по `:`, допускает не более одного `::` (zero-compression),
+/// требует 1-8 групп по 1-4 hex-цифры (ровно 8 групп без `::`), и поддерживает встроенный
+/// IPv4-хвост вида `::ffff:192.168.1.1`, проверяя его последние 32 бита через `is_valid_ipv4`.
+fn is_valid_ipv6(addr: &str) -> bool {
+    if addr.is_empty() || addr.matches("::").count() > 1 {
+        return false;
+    }
+
+    let has_compression = addr.contains("::");
+
+    // Встроенный IPv4-хвост занимает место двух hex-групп - вырезаем его перед
+    // разбором оставшейся части адреса на обычные группы.
+    let (body, embedded_ipv4_groups) = match addr.rfind(':') {
+        Some(last_colon) => {
+            let tail = &addr[last_colon + 1..];
+            if tail.contains('.') {
+                if !is_valid_ipv4(tail) {
+                    return false;
+                }
+                (&addr[..last_colon], 2usize)
+            } else {
+                (addr, 0usize)
+            }
+        }
+        None => (addr, 0usize),
+    };
+
+    if has_compression {
+        let parts: Vec<&str> = body.splitn(2, "::").collect();
+        if parts.len() != 2 {
+            return false;
+        }
+        let before: Vec<&str> = if parts[0].is_empty() { vec![] } else { parts[0].split(':').collect() };
+        let after: Vec<&str> = if parts[1].is_empty() { vec![] } else { parts[1].split(':').collect() };
+
+        if before.iter().any(|g| !is_valid_hex_group(g)) || after.iter().any(|g| !is_valid_hex_group(g)) {
+            return false;
+        }
+
+        before.len() + after.len() + embedded_ipv4_groups < 8
+    } else {
+        let groups: Vec<&str> = body.split(':').collect();
+        if groups.iter().any(|g| !is_valid_hex_group(g)) {
+            return false;
+        }
+        groups.len() + embedded_ipv4_groups == 8
+    }
+}
+
+/// Проверяет, что строка - валидное DNS-имя хоста: непустые метки из 1-63 символов
+/// (буквы, цифры, дефис, но не по краям метки), разделенные точками, итого не длиннее 253.
+fn is_valid_hostname(host: &str) -> bool {
+    !host.is_empty()
+        && host.len() <= 253
+        && host.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+        })
+}
+
+/// Строго разбирает строку как номер порта: только цифры, значение в диапазоне 1-65535.
+/// В отличие от старого `PORT_REGEX = :(\d{1,5})`, выходящие за диапазон значения
+/// отклоняются целиком, а не обрезаются до первых пяти цифр.
+fn parse_port_strict(s: &str) -> Option<u16> {
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let value: u32 = s.parse().ok()?;
+    if value == 0 || value > 65535 {
+        return None;
+    }
+    Some(value as u16)
+}
+
+/// Результат разбора authority-токена `[user@]host[:port]`.
+#[derive(Debug, Clone, PartialEq)]
+struct ParsedAuthority {
+    username: Option<String>,
+    host: String,
+    port: Option<u16>,
+}
+
+/// Разбирает токен вида `user@host:port` по образцу authority-части URI: опциональный
+/// префикс `user@`, хост - это bracketed IPv6 (`[::1]`), голый IPv6, IPv4 или DNS-имя,
+/// и опциональный `:port`. Для bracketed-хоста портом считается только `:`, идущий сразу
+/// после закрывающей `]` - это снимает неоднозначность между портом и разделителем групп
+/// голого IPv6-адреса, из-за которой старый `PORT_REGEX` мог выхватить кусок самого адреса.
+fn parse_authority(token: &str) -> Option<ParsedAuthority> {
+    let (username, rest) = match token.rsplit_once('@') {
+        Some((user, host_part)) if !user.is_empty() && !host_part.is_empty() => {
+            (Some(user.to_string()), host_part)
+        }
+        _ => (None, token),
+    };
+
+    if rest.is_empty() {
+        return None;
+    }
+
+    if let Some(after_bracket) = rest.strip_prefix('[') {
+        let close = after_bracket.find(']')?;
+        let host = &after_bracket[..close];
+        if !is_valid_ipv6(host) {
+            return None;
+        }
+        let suffix = &after_bracket[close + 1..];
+        let port = if suffix.is_empty() {
+            None
+        } else {
+            Some(parse_port_strict(suffix.strip_prefix(':')?)?)
+        };
+        return Some(ParsedAuthority { username, host: host.to_lowercase(), port });
+    }
+
+    // Голый (без скобок) IPv6 - порт неотличим от разделителя групп, поэтому не извлекается.
+    if is_valid_ipv6(rest) {
+        return Some(ParsedAuthority { username, host: rest.to_lowercase(), port: None });
+    }
+
+    if let Some((host_part, port_part)) = rest.rsplit_once(':') {
+        if is_valid_ipv4(host_part) || is_valid_hostname(host_part) {
+            let port = parse_port_strict(port_part)?;
+            return Some(ParsedAuthority { username, host: host_part.to_string(), port: Some(port) });
+        }
+        return None;
+    }
+
+    if is_valid_ipv4(rest) || is_valid_hostname(rest) {
+        return Some(ParsedAuthority { username, host: rest.to_string(), port: None });
+    }
+
+    None
+}
+
+/// Преобразует валидный IPv4-адрес в 32-битное число (старший октет - старшие биты).
+fn ipv4_to_u32(ip: &str) -> Option<u32> {
+    let mut value: u32 = 0;
+    for part in ip.split('.') {
+        let octet: u32 = part.parse().ok()?;
+        value = (value << 8) | octet;
+    }
+    Some(value)
+}
+
+fn parse_hex_group(s: &str) -> Option<u16> {
+    u16::from_str_radix(s, 16).ok()
+}
+
+/// Преобразует валидный IPv6-адрес в 128-битное число, разворачивая `::` сжатие и
+/// встроенный IPv4-хвост так же, как это делает `is_valid_ipv6`.
+fn ipv6_to_u128(addr: &str) -> Option<u128> {
+    if !is_valid_ipv6(addr) {
+        return None;
+    }
+
+    let has_compression = addr.contains("::");
+
+    let (body, embedded) = match addr.rfind(':') {
+        Some(last_colon) => {
+            let tail = &addr[last_colon + 1..];
+            if tail.contains('.') {
+                let octets: Vec<u8> =
+                    tail.split('.').map(|p| p.parse().ok()).collect::<Option<Vec<u8>>>()?;
+                let high = ((octets[0] as u16) << 8) | octets[1] as u16;
+                let low = ((octets[2] as u16) << 8) | octets[3] as u16;
+                (&addr[..last_colon], Some([high, low]))
+            } else {
+                (addr, None)
+            }
+        }
+        None => (addr, None),
+    };
+
+    let embedded_count = if embedded.is_some() { 2 } else { 0 };
+    let mut groups: Vec<u16> = Vec::with_capacity(8);
+
+    if has_compression {
+        let parts: Vec<&str> = body.splitn(2, "::").collect();
+        let before: Vec<&str> = if parts[0].is_empty() { vec![] } else { parts[0].split(':').collect() };
+        let after: Vec<&str> = if parts[1].is_empty() { vec![] } else { parts[1].split(':').collect() };
+        let before_vals: Vec<u16> = before.iter().map(|g| parse_hex_group(g)).collect::<Option<Vec<u16>>>()?;
+        let after_vals: Vec<u16> = after.iter().map(|g| parse_hex_group(g)).collect::<Option<Vec<u16>>>()?;
+        let zeros = 8usize.checked_sub(before_vals.len() + after_vals.len() + embedded_count)?;
+        groups.extend(before_vals);
+        groups.extend(std::iter::repeat(0u16).take(zeros));
+        groups.extend(after_vals);
+    } else {
+        let vals: Vec<u16> = body.split(':').map(|g| parse_hex_group(g)).collect::<Option<Vec<u16>>>()?;
+        groups.extend(vals);
+    }
+
+    if let Some(g) = embedded {
+        groups.extend(g);
+    }
+
+    if groups.len() != 8 {
+        return None;
+    }
+
+    let mut value: u128 = 0;
+    for g in groups {
+        value = (value << 16) | g as u128;
+    }
+    Some(value)
+}
+
+/// Диапазон адресов в нотации CIDR (`a.b.c.d/n` или IPv6-аналог), используемый для
+/// allow/deny-фильтрации хостов, загруженных `parse_hosts_file`.
+#[derive(Debug, Clone)]
+pub enum CidrRange {
+    V4 { network: u32, prefix_len: u8 },
+    V6 { network: u128, prefix_len: u8 },
+}
+
+impl CidrRange {
+    /// Разбирает CIDR-нотацию. Длина префикса должна быть 0-32 для IPv4 и 0-128 для IPv6.
+    pub fn parse(cidr: &str) -> Result<Self, String> {
+        let (addr, prefix_str) = cidr
+            .split_once('/')
+            .ok_or_else(|| format!("Неверный формат CIDR (ожидается a.b.c.d/n): {}", cidr))?;
+        let prefix_len: u8 = prefix_str
+            .parse()
+            .map_err(|_| format!("Неверная длина префикса в CIDR: {}", cidr))?;
+
+        if is_valid_ipv4(addr) {
+            if prefix_len > 32 {
+                return Err(format!("Длина префикса IPv4 должна быть 0-32: {}", cidr));
+            }
+            let network = ipv4_to_u32(addr).ok_or_else(|| format!("Не удалось разобрать IPv4-адрес в CIDR: {}", cidr))?;
+            Ok(CidrRange::V4 { network, prefix_len })
+        } else if is_valid_ipv6(addr) {
+            if prefix_len > 128 {
+                return Err(format!("Длина префикса IPv6 должна быть 0-128: {}", cidr));
+            }
+            let network = ipv6_to_u128(addr).ok_or_else(|| format!("Не удалось разобрать IPv6-адрес в CIDR: {}", cidr))?;
+            Ok(CidrRange::V6 { network, prefix_len })
+        } else {
+            Err(format!("Неверный адрес в CIDR: {}", cidr))
+        }
+    }
+
+    /// Проверяет, принадлежит ли адрес `ip` этому диапазону.
+    pub fn contains(&self, ip: &str) -> bool {
+        match self {
+            CidrRange::V4 { network, prefix_len } => {
+                let Some(addr) = ipv4_to_u32(ip) else { return false };
+                let mask: u32 = if *prefix_len == 0 { 0 } else { u32::MAX << (32 - prefix_len) };
+                (addr & mask) == (network & mask)
+            }
+            CidrRange::V6 { network, prefix_len } => {
+                let Some(addr) = ipv6_to_u128(ip) else { return false };
+                let mask: u128 = if *prefix_len == 0 { 0 } else { u128::MAX << (128 - prefix_len) };
+                (addr & mask) == (network & mask)
+            }
+        }
+    }
+}
+
+/// Allow/deny-фильтр по CIDR-диапазонам для хостов, загруженных из файла. Deny имеет
+/// приоритет над allow: хост проходит, только если он не попал ни в один deny-диапазон,
+/// и (если allow непустой) попал хотя бы в один allow-диапазон.
+#[derive(Debug, Clone, Default)]
+pub struct IpFilter {
+    pub allow: Vec<CidrRange>,
+    pub deny: Vec<CidrRange>,
+}
+
+impl IpFilter {
+    pub fn permits(&self, ip: &str) -> bool {
+        if self.deny.iter().any(|range| range.contains(ip)) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(|range| range.contains(ip))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HostEntry {
     pub ip: String,
     pub port: Option<u16>,
     pub hostname: Option<String>,
+    /// Имя пользователя из authority-формы `user@host[:port]`, если оно было указано.
+    #[serde(default)]
+    pub username: Option<String>,
     pub metadata: std::collections::HashMap<String, String>,
 }
 
@@ -75,6 +355,172 @@ pub fn parse_hosts_file(file_path: &str) -> AppResult<Vec<HostEntry>> {
     }
 }
 
+/// Кэширующий DNS-резолвер для одного прогона разбора файла хостов - чтобы несколько строк
+/// с одним и тем же именем/адресом не приводили к повторным сетевым запросам.
+struct DnsCache {
+    resolver: hickory_resolver::TokioAsyncResolver,
+    forward: std::collections::HashMap<String, Vec<String>>,
+    reverse: std::collections::HashMap<String, String>,
+}
+
+impl DnsCache {
+    fn new() -> Self {
+        Self {
+            resolver: hickory_resolver::TokioAsyncResolver::tokio(
+                hickory_resolver::config::ResolverConfig::default(),
+                hickory_resolver::config::ResolverOpts::default(),
+            ),
+            forward: std::collections::HashMap::new(),
+            reverse: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Прямое разрешение A/AAAA-записей имени хоста, с кэшированием на время прогона.
+    async fn resolve_forward(&mut self, hostname: &str) -> Vec<String> {
+        if let Some(cached) = self.forward.get(hostname) {
+            return cached.clone();
+        }
+
+        let addrs = match self.resolver.lookup_ip(hostname).await {
+            Ok(lookup) => lookup.iter().map(|addr| addr.to_string()).collect::<Vec<_>>(),
+            Err(e) => {
+                log::warn!("Не удалось разрешить имя хоста {}: {}", hostname, e);
+                Vec::new()
+            }
+        };
+
+        self.forward.insert(hostname.to_string(), addrs.clone());
+        addrs
+    }
+
+    /// Обратное PTR-разрешение IP-адреса в имя хоста, с кэшированием на время прогона.
+    async fn resolve_reverse(&mut self, ip: &str) -> Option<String> {
+        if let Some(cached) = self.reverse.get(ip) {
+            return Some(cached.clone());
+        }
+
+        let addr: std::net::IpAddr = ip.parse().ok()?;
+        let hostname = match self.resolver.reverse_lookup(addr).await {
+            Ok(lookup) => lookup
+                .iter()
+                .next()
+                .map(|name| name.to_string().trim_end_matches('.').to_string()),
+            Err(e) => {
+                log::warn!("Не удалось выполнить обратное разрешение для {}: {}", ip, e);
+                None
+            }
+        };
+
+        if let Some(ref name) = hostname {
+            self.reverse.insert(ip.to_string(), name.clone());
+        }
+        hostname
+    }
+}
+
+/// Возвращает токены текстового файла хостов, которые разбираются как DNS-имя, а не
+/// IP-литерал - используется только при `resolve_dns = true` в `parse_hosts_file_with_dns`.
+/// Для CSV/Excel границу между "именем хоста" и произвольным текстом в колонке метаданных
+/// провести надежно нельзя, поэтому разворачивание имен в адреса поддерживается только
+/// для построчного текстового формата.
+fn collect_hostname_tokens(file_path: &str) -> AppResult<Vec<String>> {
+    let content = std::fs::read_to_string(file_path)
+        .map_err(|e| AppError::FileError(format!("Failed to read file: {}", e)))?;
+
+    let mut hostnames = Vec::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        for token in line.split_whitespace() {
+            if let Some(authority) = parse_authority(token) {
+                if !is_valid_ipv4(&authority.host) && !is_valid_ipv6(&authority.host) {
+                    hostnames.push(authority.host);
+                }
+            }
+        }
+    }
+    Ok(hostnames)
+}
+
+/// Отбрасывает записи, не прошедшие allow/deny-фильтр по CIDR. Каждый отклоненный хост
+/// логируется. Общая часть `parse_hosts_file_filtered` и `parse_hosts_file_with_dns`.
+fn apply_ip_filter(hosts: Vec<HostEntry>, filter: &IpFilter) -> Vec<HostEntry> {
+    hosts
+        .into_iter()
+        .filter(|host| {
+            let permitted = filter.permits(&host.ip);
+            if !permitted {
+                log::info!("Хост {} отклонен фильтром CIDR", host.ip);
+            }
+            permitted
+        })
+        .collect()
+}
+
+/// Разбирает файл с хостами так же, как `parse_hosts_file`, и, если `resolve_dns` включен,
+/// дополнительно: (1) для текстовых файлов разворачивает строки с DNS-именами в одну
+/// `HostEntry` на каждый разрешенный A/AAAA-адрес, и (2) для всех IP-литералов пытается
+/// обратным PTR-запросом заполнить `hostname`. Результаты кэшируются на время одного вызова,
+/// чтобы повторяющиеся имена/адреса не резолвились повторно. Ошибка разрешения одного имени
+/// не прерывает разбор всего файла - такая строка пропускается (а IP-литерал без обратного
+/// резолва просто остается с `hostname: None`, как и раньше).
+///
+/// Если передан `filter`, он применяется последним - после DNS-разворачивания имен в
+/// адреса, чтобы allow/deny-список видел и адреса, полученные из DNS, а не только то, что
+/// было literal IP в исходном файле.
+pub async fn parse_hosts_file_with_dns(file_path: &str, resolve_dns: bool, filter: Option<&IpFilter>) -> AppResult<Vec<HostEntry>> {
+    let mut hosts = parse_hosts_file(file_path)?;
+
+    if resolve_dns {
+        let mut cache = DnsCache::new();
+
+        for host in hosts.iter_mut() {
+            if let Some(hostname) = cache.resolve_reverse(&host.ip).await {
+                host.hostname = Some(hostname);
+            }
+        }
+
+        let is_txt = Path::new(file_path)
+            .extension()
+            .and_then(|s| s.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("txt"));
+
+        if is_txt {
+            for hostname in collect_hostname_tokens(file_path)? {
+                let addrs = cache.resolve_forward(&hostname).await;
+                if addrs.is_empty() {
+                    log::warn!("Имя хоста {} не разрешилось ни в один адрес, пропущено", hostname);
+                    continue;
+                }
+                for addr in addrs {
+                    hosts.push(HostEntry {
+                        ip: addr,
+                        port: None,
+                        hostname: Some(hostname.clone()),
+                        username: None,
+                        metadata: std::collections::HashMap::new(),
+                    });
+                }
+            }
+        }
+    }
+
+    if let Some(filter) = filter {
+        hosts = apply_ip_filter(hosts, filter);
+    }
+
+    Ok(hosts)
+}
+
+/// Разбирает файл с хостами так же, как `parse_hosts_file`, и дополнительно отбрасывает
+/// записи, не прошедшие allow/deny-фильтр по CIDR.
+pub fn parse_hosts_file_filtered(file_path: &str, filter: &IpFilter) -> AppResult<Vec<HostEntry>> {
+    let hosts = parse_hosts_file(file_path)?;
+    Ok(apply_ip_filter(hosts, filter))
+}
+
 fn parse_txt_file(file_path: &str) -> AppResult<Vec<HostEntry>> {
     let content = std::fs::read_to_string(file_path)
         .map_err(|e| AppError::FileError(format!("Failed to read file: {}", e)))?;
@@ -88,21 +534,31 @@ fn parse_txt_file(file_path: &str) -> AppResult<Vec<HostEntry>> {
             continue;
         }
 
-        // Находим все IP адреса в строке (для случаев, когда несколько IP через пробел)
-        for captures in ip_regex.find_iter(line) {
-            let ip = captures.as_str().to_string();
-            // Проверяем валидность IP адреса (каждый октет должен быть <= 255)
-            if !is_valid_ipv4(&ip) {
-                log::warn!("Пропущен невалидный IP адрес: {}", ip);
-                continue;
+        // Каждый токен строки разбираем как authority (`[user@]host[:port]`) - так
+        // один токен `user@[::1]:2222` сразу дает имя пользователя, адрес и порт, а
+        // bracketed-форма снимает неоднозначность между портом и группами IPv6.
+        for token in line.split_whitespace() {
+            match parse_authority(token) {
+                Some(authority) if is_valid_ipv4(&authority.host) || is_valid_ipv6(&authority.host) => {
+                    hosts.push(HostEntry {
+                        ip: authority.host,
+                        port: authority.port,
+                        hostname: None,
+                        username: authority.username,
+                        metadata: std::collections::HashMap::new(),
+                    });
+                }
+                // Токен разобрался как authority с DNS-именем вместо IP-литерала -
+                // разрешение имен в адреса не входит в эту задачу, пропускаем молча.
+                Some(_) => {}
+                None => {
+                    if ip_regex.is_match(token) {
+                        log::warn!("Пропущен невалидный IP адрес: {}", token);
+                    } else if token.contains(':') {
+                        log::warn!("Пропущен невалидный IPv6 адрес: {}", token);
+                    }
+                }
             }
-            let port = extract_port(line);
-            hosts.push(HostEntry {
-                ip,
-                port,
-                hostname: None,
-                metadata: std::collections::HashMap::new(),
-            });
         }
     }
 
@@ -127,10 +583,29 @@ fn parse_csv_file(file_path: &str) -> AppResult<Vec<HostEntry>> {
         let mut metadata = std::collections::HashMap::new();
         let mut ip: Option<String> = None;
         let mut port: Option<u16> = None;
+        let mut username: Option<String> = None;
         let hostname: Option<String> = None;
 
         for (i, field) in record.iter().enumerate() {
-            if ip_regex.is_match(field) && is_valid_ipv4(field) {
+            // Поля вида `user@host:port` или `[::1]:22` разбираем authority-парсером,
+            // а не старой парой "IPv6-проверка + отдельный порт" - это снимает
+            // неоднозначность между портом и группами голого IPv6-адреса.
+            if field.contains(':') || field.contains('[') || field.contains('@') {
+                if let Some(authority) = parse_authority(field) {
+                    if ip.is_none() && (is_valid_ipv4(&authority.host) || is_valid_ipv6(&authority.host)) {
+                        ip = Some(authority.host);
+                        port = port.or(authority.port);
+                        username = username.or(authority.username);
+                        continue;
+                    }
+                }
+                if ip_regex.is_match(field) {
+                    // IP найден по regex, но не прошел валидацию октетов
+                    log::warn!("Пропущен невалидный IP адрес в CSV: {}", field);
+                } else if !field.is_empty() {
+                    metadata.insert(format!("column_{}", i), field.to_string());
+                }
+            } else if ip_regex.is_match(field) && is_valid_ipv4(field) {
                 ip = Some(field.to_string());
             } else if ip_regex.is_match(field) {
                 // IP найден по regex, но не прошел валидацию октетов
@@ -149,6 +624,7 @@ fn parse_csv_file(file_path: &str) -> AppResult<Vec<HostEntry>> {
                 ip: ip_addr,
                 port,
                 hostname,
+                username,
                 metadata,
             });
         }
@@ -179,12 +655,30 @@ fn parse_excel_file(file_path: &str) -> AppResult<Vec<HostEntry>> {
         let mut metadata = std::collections::HashMap::new();
         let mut ip: Option<String> = None;
         let mut port: Option<u16> = None;
+        let mut username: Option<String> = None;
         let hostname: Option<String> = None;
 
         for (col_idx, cell) in row.iter().enumerate() {
             let cell_value = cell.to_string();
-            
-            if ip_regex.is_match(&cell_value) && is_valid_ipv4(&cell_value) {
+
+            // См. аналогичную ветку в `parse_csv_file`: authority-парсер снимает
+            // неоднозначность между портом и группами голого IPv6-адреса.
+            if cell_value.contains(':') || cell_value.contains('[') || cell_value.contains('@') {
+                if let Some(authority) = parse_authority(&cell_value) {
+                    if ip.is_none() && (is_valid_ipv4(&authority.host) || is_valid_ipv6(&authority.host)) {
+                        ip = Some(authority.host);
+                        port = port.or(authority.port);
+                        username = username.or(authority.username);
+                        continue;
+                    }
+                }
+                if ip_regex.is_match(&cell_value) {
+                    // IP найден по regex, но не прошел валидацию октетов
+                    log::warn!("Пропущен невалидный IP адрес в Excel: {}", cell_value);
+                } else if !cell_value.is_empty() {
+                    metadata.insert(format!("column_{}", col_idx), cell_value);
+                }
+            } else if ip_regex.is_match(&cell_value) && is_valid_ipv4(&cell_value) {
                 ip = Some(cell_value.clone());
             } else if ip_regex.is_match(&cell_value) {
                 // IP найден по regex, но не прошел валидацию октетов
@@ -203,6 +697,7 @@ fn parse_excel_file(file_path: &str) -> AppResult<Vec<HostEntry>> {
                 ip: ip_addr,
                 port,
                 hostname,
+                username,
                 metadata,
             });
         }
@@ -211,14 +706,6 @@ fn parse_excel_file(file_path: &str) -> AppResult<Vec<HostEntry>> {
     Ok(hosts)
 }
 
-fn extract_port(line: &str) -> Option<u16> {
-    let port_regex = get_port_regex();
-    port_regex
-        .captures(line)
-        .and_then(|c| c.get(1))
-        .and_then(|m| m.as_str().parse().ok())
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -254,4 +741,189 @@ mod tests {
         assert!(!is_valid_ipv4("192.168.1."));         // Пустой октет
         assert!(!is_valid_ipv4(".168.1.1"));           // Пустой первый октет
     }
+
+    #[test]
+    fn test_valid_ipv6() {
+        // Валидные адреса
+        assert!(is_valid_ipv6("2001:db8::1"));
+        assert!(is_valid_ipv6("::1"));
+        assert!(is_valid_ipv6("::"));
+        assert!(is_valid_ipv6("fe80::1"));
+        assert!(is_valid_ipv6("2001:0db8:0000:0000:0000:ff00:0042:8329"));
+        assert!(is_valid_ipv6("1:2:3:4:5:6:7:8"));
+        // Встроенный IPv4-хвост
+        assert!(is_valid_ipv6("::ffff:192.168.1.1"));
+        assert!(is_valid_ipv6("2001:db8::ffff:192.168.1.1"));
+
+        // Невалидные адреса
+        assert!(!is_valid_ipv6(""));
+        assert!(!is_valid_ipv6("2001::db8::1"));          // двойной "::"
+        assert!(!is_valid_ipv6("12345::1"));              // группа длиннее 4 hex-цифр
+        assert!(!is_valid_ipv6("1:2:3:4:5:6:7:8:9"));      // слишком много групп
+        assert!(!is_valid_ipv6("gggg::1"));               // не hex-символы
+        assert!(!is_valid_ipv6("::ffff:999.168.1.1"));    // невалидный встроенный IPv4
+        assert!(!is_valid_ipv6("1:2:3:4:5:6:7"));         // 7 групп без "::"
+        assert!(!is_valid_ipv6("192.168.1.1"));           // это IPv4, не IPv6
+    }
+
+    #[test]
+    fn test_parse_txt_bracketed_ipv6_with_port() {
+        let dir = std::env::temp_dir();
+        let path = dir.join("ssh_executor_test_hosts_ipv6.txt");
+        std::fs::write(&path, "[2001:db8::1]:2222 prod-db\n::ffff:10.0.0.1\n192.168.1.1:22\n").unwrap();
+
+        let hosts = parse_txt_file(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert!(hosts.iter().any(|h| h.ip == "2001:db8::1" && h.port == Some(2222)));
+        assert!(hosts.iter().any(|h| h.ip == "::ffff:10.0.0.1" && h.port.is_none()));
+        assert!(hosts.iter().any(|h| h.ip == "192.168.1.1" && h.port == Some(22)));
+    }
+
+    #[test]
+    fn test_parse_port_strict() {
+        assert_eq!(parse_port_strict("22"), Some(22));
+        assert_eq!(parse_port_strict("65535"), Some(65535));
+        assert_eq!(parse_port_strict("1"), Some(1));
+
+        assert_eq!(parse_port_strict("0"), None);               // порт 0 недопустим
+        assert_eq!(parse_port_strict("99999"), None);            // вне диапазона u16
+        assert_eq!(parse_port_strict("123456"), None);           // раньше regex вернул бы усеченный порт "12345"
+        assert_eq!(parse_port_strict(""), None);
+        assert_eq!(parse_port_strict("22a"), None);
+    }
+
+    #[test]
+    fn test_parse_authority_username_and_bracketed_ipv6() {
+        let authority = parse_authority("user@[::1]:2222").unwrap();
+        assert_eq!(authority.username.as_deref(), Some("user"));
+        assert_eq!(authority.host, "::1");
+        assert_eq!(authority.port, Some(2222));
+    }
+
+    #[test]
+    fn test_parse_authority_ipv4_with_port() {
+        let authority = parse_authority("alice@10.0.0.5:22").unwrap();
+        assert_eq!(authority.username.as_deref(), Some("alice"));
+        assert_eq!(authority.host, "10.0.0.5");
+        assert_eq!(authority.port, Some(22));
+    }
+
+    #[test]
+    fn test_parse_authority_bare_ipv6_has_no_port() {
+        // Без скобок `:` неотличим от разделителя групп - порт не извлекается.
+        let authority = parse_authority("2001:db8::1").unwrap();
+        assert_eq!(authority.host, "2001:db8::1");
+        assert_eq!(authority.port, None);
+    }
+
+    #[test]
+    fn test_parse_authority_rejects_out_of_range_port() {
+        // Раньше `PORT_REGEX = :(\d{1,5})` захватывал только первые 5 цифр "123456",
+        // тихо превращая порт в "12345" вместо отказа - теперь это явный отказ.
+        assert!(parse_authority("10.0.0.1:123456").is_none());
+        assert!(parse_authority("[2001:db8::1]:99999").is_none());
+        assert!(parse_authority("10.0.0.1:0").is_none());
+    }
+
+    #[test]
+    fn test_parse_txt_authority_with_username_round_trips() {
+        let dir = std::env::temp_dir();
+        let path = dir.join("ssh_executor_test_hosts_authority.txt");
+        std::fs::write(&path, "user@[::1]:2222\n").unwrap();
+
+        let hosts = parse_txt_file(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert!(hosts
+            .iter()
+            .any(|h| h.ip == "::1" && h.port == Some(2222) && h.username.as_deref() == Some("user")));
+    }
+
+    #[test]
+    fn test_cidr_range_ipv4() {
+        let range = CidrRange::parse("10.0.0.0/8").unwrap();
+        assert!(range.contains("10.1.2.3"));
+        assert!(!range.contains("11.1.2.3"));
+
+        let single_host = CidrRange::parse("192.168.1.1/32").unwrap();
+        assert!(single_host.contains("192.168.1.1"));
+        assert!(!single_host.contains("192.168.1.2"));
+
+        let everything = CidrRange::parse("0.0.0.0/0").unwrap();
+        assert!(everything.contains("1.2.3.4"));
+    }
+
+    #[test]
+    fn test_cidr_range_ipv6() {
+        let range = CidrRange::parse("2001:db8::/32").unwrap();
+        assert!(range.contains("2001:db8::1"));
+        assert!(range.contains("2001:db8:ffff::1"));
+        assert!(!range.contains("2001:db9::1"));
+
+        // Встроенный IPv4-хвост должен участвовать в маскировании наравне с обычными группами
+        let embedded = CidrRange::parse("::ffff:10.0.0.0/104").unwrap();
+        assert!(embedded.contains("::ffff:10.1.2.3"));
+        assert!(!embedded.contains("::ffff:11.1.2.3"));
+    }
+
+    #[test]
+    fn test_cidr_range_rejects_invalid_input() {
+        assert!(CidrRange::parse("10.0.0.0/33").is_err());
+        assert!(CidrRange::parse("2001:db8::/129").is_err());
+        assert!(CidrRange::parse("not-an-ip/8").is_err());
+        assert!(CidrRange::parse("10.0.0.0").is_err());
+    }
+
+    #[test]
+    fn test_ip_filter_deny_takes_precedence() {
+        let filter = IpFilter {
+            allow: vec![CidrRange::parse("10.0.0.0/8").unwrap()],
+            deny: vec![CidrRange::parse("10.0.0.0/24").unwrap()],
+        };
+
+        assert!(!filter.permits("10.0.0.5")); // в deny - отклонен, даже если входит в allow
+        assert!(filter.permits("10.0.1.5")); // вне deny, внутри allow
+        assert!(!filter.permits("192.168.1.1")); // вне allow
+    }
+
+    #[test]
+    fn test_ip_filter_empty_allow_means_allow_all() {
+        let filter = IpFilter {
+            allow: vec![],
+            deny: vec![CidrRange::parse("10.0.0.0/8").unwrap()],
+        };
+
+        assert!(filter.permits("192.168.1.1"));
+        assert!(!filter.permits("10.0.0.1"));
+    }
+
+    #[test]
+    fn test_parse_hosts_file_filtered() {
+        let dir = std::env::temp_dir();
+        let path = dir.join("ssh_executor_test_hosts_filtered.txt");
+        std::fs::write(&path, "10.0.0.1\n192.168.1.1\n").unwrap();
+
+        let filter = IpFilter {
+            allow: vec![CidrRange::parse("10.0.0.0/8").unwrap()],
+            deny: vec![],
+        };
+        let hosts = parse_hosts_file_filtered(path.to_str().unwrap(), &filter).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(hosts.len(), 1);
+        assert_eq!(hosts[0].ip, "10.0.0.1");
+    }
+
+    #[test]
+    fn test_collect_hostname_tokens_ignores_ip_literals() {
+        let dir = std::env::temp_dir();
+        let path = dir.join("ssh_executor_test_hostnames.txt");
+        std::fs::write(&path, "db01.example.com\n10.0.0.1\n# comment\nweb.example.com:2222\n").unwrap();
+
+        let hostnames = collect_hostname_tokens(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(hostnames, vec!["db01.example.com".to_string(), "web.example.com".to_string()]);
+    }
 }
@@ -0,0 +1,374 @@
+use crate::security::{decrypt_password, encrypt_password, EncryptedData, ZeroizingString};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Каталог, где хранятся файлы приватных/публичных ключей, и путь к JSON-реестру
+/// метаданных ключей (passphrase в реестре хранится зашифрованной, как и пароли хостов)
+static KEYS_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+static KEY_STORE_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Метаданные ключа, которые можно безопасно отдавать в GUI - приватный материал
+/// и зашифрованный passphrase в эту структуру не попадают
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SshKeyInfo {
+    pub id: String,
+    pub name: String,
+    pub key_type: String, // "ed25519" | "rsa"
+    pub public_key: String,
+    pub fingerprint: String,
+    pub has_passphrase: bool,
+    pub created_at: String,
+}
+
+// Полная запись реестра, включая путь к приватному ключу и зашифрованный passphrase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredSshKey {
+    id: String,
+    name: String,
+    key_type: String,
+    public_key: String,
+    fingerprint: String,
+    private_key_path: String,
+    passphrase: Option<EncryptedData>,
+    created_at: String,
+}
+
+impl From<&StoredSshKey> for SshKeyInfo {
+    fn from(stored: &StoredSshKey) -> Self {
+        SshKeyInfo {
+            id: stored.id.clone(),
+            name: stored.name.clone(),
+            key_type: stored.key_type.clone(),
+            public_key: stored.public_key.clone(),
+            fingerprint: stored.fingerprint.clone(),
+            has_passphrase: stored.passphrase.is_some(),
+            created_at: stored.created_at.clone(),
+        }
+    }
+}
+
+/// Инициализирует каталог хранения ключей и путь к реестру метаданных. Вызывается
+/// один раз из `main.rs::setup`, аналогично `security::init_encryption`.
+pub fn init_key_store(app_data_dir: PathBuf) {
+    let keys_dir = app_data_dir.join("ssh_keys");
+    if let Err(e) = std::fs::create_dir_all(&keys_dir) {
+        log::error!("Failed to create SSH keys directory: {}", e);
+    }
+    if let Ok(mut guard) = KEYS_DIR.lock() {
+        *guard = Some(keys_dir);
+    }
+    if let Ok(mut guard) = KEY_STORE_PATH.lock() {
+        *guard = Some(app_data_dir.join("ssh_keys.json"));
+    }
+}
+
+fn keys_dir() -> Result<PathBuf, String> {
+    KEYS_DIR
+        .lock()
+        .map_err(|_| "Не удалось заблокировать мьютекс каталога ключей".to_string())?
+        .clone()
+        .ok_or_else(|| "Хранилище SSH-ключей не инициализировано".to_string())
+}
+
+fn store_path() -> Result<PathBuf, String> {
+    KEY_STORE_PATH
+        .lock()
+        .map_err(|_| "Не удалось заблокировать мьютекс реестра ключей".to_string())?
+        .clone()
+        .ok_or_else(|| "Хранилище SSH-ключей не инициализировано".to_string())
+}
+
+fn load_store() -> Result<Vec<StoredSshKey>, String> {
+    let path = store_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read key store: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse key store: {}", e))
+}
+
+fn save_store(keys: &[StoredSshKey]) -> Result<(), String> {
+    let path = store_path()?;
+    let json = serde_json::to_string_pretty(keys).map_err(|e| format!("Failed to serialize key store: {}", e))?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write key store: {}", e))
+}
+
+fn run_ssh_keygen(args: &[&std::ffi::OsStr]) -> Result<std::process::Output, String> {
+    std::process::Command::new("ssh-keygen")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Не удалось запустить ssh-keygen: {}", e))
+}
+
+fn fingerprint_of(private_key_path: &std::path::Path) -> Result<String, String> {
+    let output = run_ssh_keygen(&[std::ffi::OsStr::new("-lf"), private_key_path.as_os_str()])?;
+    if !output.status.success() {
+        return Err(format!(
+            "ssh-keygen -lf завершился с ошибкой: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Генерирует новую пару ключей (RSA 4096 или Ed25519) через системный `ssh-keygen`,
+/// шифрует passphrase (если указан) и сохраняет метаданные в реестр
+pub fn create_ssh_key(name: String, key_type: String, passphrase: Option<String>) -> Result<SshKeyInfo, String> {
+    let dir = keys_dir()?;
+    let id = Uuid::new_v4().to_string();
+    let private_key_path = dir.join(&id);
+    let public_key_path = private_key_path.with_extension("pub");
+
+    let key_type_flag = if key_type.eq_ignore_ascii_case("rsa") { "rsa" } else { "ed25519" };
+    let passphrase_arg = passphrase.clone().unwrap_or_default();
+
+    let mut args: Vec<&std::ffi::OsStr> = vec![
+        std::ffi::OsStr::new("-t"),
+        std::ffi::OsStr::new(key_type_flag),
+        std::ffi::OsStr::new("-f"),
+        private_key_path.as_os_str(),
+        std::ffi::OsStr::new("-N"),
+        std::ffi::OsStr::new(&passphrase_arg),
+        std::ffi::OsStr::new("-C"),
+        std::ffi::OsStr::new(&name),
+        std::ffi::OsStr::new("-q"),
+    ];
+    if key_type_flag == "rsa" {
+        args.push(std::ffi::OsStr::new("-b"));
+        args.push(std::ffi::OsStr::new("4096"));
+    }
+
+    let output = run_ssh_keygen(&args)?;
+    if !output.status.success() {
+        return Err(format!("ssh-keygen завершился с ошибкой: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    let public_key = std::fs::read_to_string(&public_key_path)
+        .map_err(|e| format!("Failed to read generated public key: {}", e))?
+        .trim()
+        .to_string();
+    let fingerprint = fingerprint_of(&private_key_path)?;
+
+    // ssh-keygen умеет писать сгенерированный ключ только на диск, поэтому он какое-то
+    // время лежит там в открытом виде - сразу после того, как он больше не нужен в этом
+    // виде (fingerprint снят), шифруем его "в покое" (age) и оставляем на диске только
+    // зашифрованную версию (см. age_vault::encrypt_at_rest)
+    let plaintext_key = std::fs::read(&private_key_path)
+        .map_err(|e| format!("Failed to read generated private key: {}", e))?;
+    let encrypted_key = crate::age_vault::encrypt_at_rest(&plaintext_key).map_err(|e| e.to_string())?;
+    std::fs::write(&private_key_path, &encrypted_key)
+        .map_err(|e| format!("Failed to persist encrypted private key: {}", e))?;
+
+    let encrypted_passphrase = match passphrase {
+        Some(ref p) if !p.is_empty() => Some(encrypt_password(p)?),
+        _ => None,
+    };
+
+    let stored = StoredSshKey {
+        id,
+        name,
+        key_type: key_type_flag.to_string(),
+        public_key,
+        fingerprint,
+        private_key_path: private_key_path.to_string_lossy().to_string(),
+        passphrase: encrypted_passphrase,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let mut keys = load_store()?;
+    let info = SshKeyInfo::from(&stored);
+    keys.push(stored);
+    save_store(&keys)?;
+
+    Ok(info)
+}
+
+/// Импортирует существующий приватный ключ (его содержимое) в хранилище под новым `id`.
+/// Публичный ключ выводится через `ssh-keygen -y`, что работает только для
+/// ключей без passphrase или когда passphrase передан и ключ поддерживает её для `-y`
+/// в установленной версии OpenSSH - для зашифрованных ключей без успешного вывода
+/// публичная часть остается пустой строкой, это честно отражается в метаданных.
+pub fn import_ssh_key(name: String, private_key_pem: String, passphrase: Option<String>) -> Result<SshKeyInfo, String> {
+    let dir = keys_dir()?;
+    let id = Uuid::new_v4().to_string();
+    let private_key_path = dir.join(&id);
+
+    // ssh-keygen -y/-lf читают ключ только с диска - материализуем его в гарантированно
+    // удаляемом временном файле (см. age_vault::TempKeyFile) ровно на время извлечения
+    // публичной части и фингерпринта, а на постоянное хранение кладем только
+    // зашифрованную "в покое" (age) версию, не открытый PEM
+    let temp_key_file = crate::age_vault::TempKeyFile::write(private_key_pem.as_bytes()).map_err(|e| e.to_string())?;
+
+    let public_key = {
+        let output = run_ssh_keygen(&[std::ffi::OsStr::new("-y"), std::ffi::OsStr::new("-f"), temp_key_file.path().as_os_str()]);
+        match output {
+            Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout).trim().to_string(),
+            _ => String::new(),
+        }
+    };
+    let fingerprint = fingerprint_of(temp_key_file.path()).unwrap_or_default();
+    let key_type = if private_key_pem.contains("OPENSSH PRIVATE KEY") || public_key.starts_with("ssh-ed25519") {
+        "ed25519".to_string()
+    } else {
+        "rsa".to_string()
+    };
+
+    let encrypted_key = crate::age_vault::encrypt_at_rest(private_key_pem.as_bytes()).map_err(|e| e.to_string())?;
+    std::fs::write(&private_key_path, &encrypted_key)
+        .map_err(|e| format!("Failed to write imported key: {}", e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(metadata) = std::fs::metadata(&private_key_path) {
+            let mut perms = metadata.permissions();
+            perms.set_mode(0o600);
+            let _ = std::fs::set_permissions(&private_key_path, perms);
+        }
+    }
+
+    let encrypted_passphrase = match passphrase {
+        Some(ref p) if !p.is_empty() => Some(encrypt_password(p)?),
+        _ => None,
+    };
+
+    let stored = StoredSshKey {
+        id,
+        name,
+        key_type,
+        public_key,
+        fingerprint,
+        private_key_path: private_key_path.to_string_lossy().to_string(),
+        passphrase: encrypted_passphrase,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let mut keys = load_store()?;
+    let info = SshKeyInfo::from(&stored);
+    keys.push(stored);
+    save_store(&keys)?;
+
+    Ok(info)
+}
+
+/// Возвращает метаданные всех сохраненных ключей (без приватного материала)
+pub fn list_ssh_keys() -> Result<Vec<SshKeyInfo>, String> {
+    Ok(load_store()?.iter().map(SshKeyInfo::from).collect())
+}
+
+/// Удаляет ключ из реестра и стирает его файлы с диска
+pub fn delete_ssh_key(key_id: &str) -> Result<(), String> {
+    let mut keys = load_store()?;
+    let position = keys
+        .iter()
+        .position(|k| k.id == key_id)
+        .ok_or_else(|| format!("Ключ с id {} не найден", key_id))?;
+    let removed = keys.remove(position);
+
+    let private_path = PathBuf::from(&removed.private_key_path);
+    let _ = std::fs::remove_file(&private_path);
+    let _ = std::fs::remove_file(private_path.with_extension("pub"));
+
+    save_store(&keys)
+}
+
+/// Меняет passphrase существующего ключа через `ssh-keygen -p`, перешифровывая его
+/// в реестре. Пустая строка `new_passphrase` снимает защиту ключа.
+pub fn reset_key_passphrase(key_id: &str, old_passphrase: Option<String>, new_passphrase: Option<String>) -> Result<(), String> {
+    let mut keys = load_store()?;
+    let stored = keys
+        .iter_mut()
+        .find(|k| k.id == key_id)
+        .ok_or_else(|| format!("Ключ с id {} не найден", key_id))?;
+
+    // Ключ на диске хранится зашифрованным - расшифровываем во временный файл, чтобы
+    // ssh-keygen -p смог перешифровать его passphrase как обычно, затем шифруем
+    // результат обратно и сохраняем только его (временный файл удаляется автоматически)
+    let encrypted = std::fs::read(&stored.private_key_path)
+        .map_err(|e| format!("Failed to read stored private key: {}", e))?;
+    let decrypted = crate::age_vault::decrypt_at_rest(&encrypted).map_err(|e| e.to_string())?;
+    let temp_key_file = crate::age_vault::TempKeyFile::write(&decrypted).map_err(|e| e.to_string())?;
+
+    let old_arg = old_passphrase.unwrap_or_default();
+    let new_arg = new_passphrase.clone().unwrap_or_default();
+
+    let output = run_ssh_keygen(&[
+        std::ffi::OsStr::new("-p"),
+        std::ffi::OsStr::new("-f"),
+        temp_key_file.path().as_os_str(),
+        std::ffi::OsStr::new("-P"),
+        std::ffi::OsStr::new(&old_arg),
+        std::ffi::OsStr::new("-N"),
+        std::ffi::OsStr::new(&new_arg),
+    ])?;
+    if !output.status.success() {
+        return Err(format!("ssh-keygen -p завершился с ошибкой: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    let rekeyed_plaintext = std::fs::read(temp_key_file.path())
+        .map_err(|e| format!("Failed to read re-keyed private key: {}", e))?;
+    let rekeyed_encrypted = crate::age_vault::encrypt_at_rest(&rekeyed_plaintext).map_err(|e| e.to_string())?;
+    std::fs::write(&stored.private_key_path, &rekeyed_encrypted)
+        .map_err(|e| format!("Failed to persist re-keyed private key: {}", e))?;
+
+    stored.passphrase = match new_passphrase {
+        Some(ref p) if !p.is_empty() => Some(encrypt_password(p)?),
+        _ => None,
+    };
+
+    save_store(&keys)
+}
+
+/// Расшифровывает приватный ключ по его id в гарантированно удаляемый временный файл -
+/// используется `ssh::connect_once` для `AuthMethod::ManagedKey`, чтобы конфигурация
+/// хоста ссылалась на управляемый ключ по id вместо протаскивания пути к файлу на
+/// каждый хост. На диске ключ хранится в зашифрованном (age) виде и расшифровывается
+/// только на время, необходимое libssh2 для чтения файла в `userauth_pubkey_file`.
+pub fn resolve_key_path(key_id: &str) -> Result<crate::age_vault::TempKeyFile, String> {
+    let keys = load_store()?;
+    let stored = keys
+        .iter()
+        .find(|k| k.id == key_id)
+        .ok_or_else(|| format!("Ключ с id {} не найден", key_id))?;
+
+    let encrypted = std::fs::read(&stored.private_key_path)
+        .map_err(|e| format!("Failed to read stored private key: {}", e))?;
+    let decrypted = crate::age_vault::decrypt_at_rest(&encrypted).map_err(|e| e.to_string())?;
+    crate::age_vault::TempKeyFile::write(&decrypted).map_err(|e| e.to_string())
+}
+
+/// Перешифровывает passphrase каждого сохраненного ключа под ключом ротации, начатой
+/// `security::rotate_encryption_key` - единственное место, где `EncryptedData` реального
+/// реестра попадает на диск напрямую (в отличие от `vault.rs`, чьи секреты заново
+/// заворачиваются под собственным ключом хранилища перед записью в БД). Возвращает число
+/// фактически перешифрованных записей; вызывается фронтендом между `start_encryption_key_rotation`
+/// и `finish_encryption_key_rotation`, иначе записи с паролем от старого ключа станут нечитаемы.
+pub fn re_encrypt_all_passphrases() -> Result<usize, String> {
+    let mut keys = load_store()?;
+    let mut re_encrypted = 0usize;
+
+    for stored in keys.iter_mut() {
+        if let Some(passphrase) = &stored.passphrase {
+            let new_passphrase = crate::security::re_encrypt(passphrase).map_err(|e| e.to_string())?;
+            if new_passphrase.key_id() != passphrase.key_id() {
+                re_encrypted += 1;
+            }
+            stored.passphrase = Some(new_passphrase);
+        }
+    }
+
+    save_store(&keys)?;
+    Ok(re_encrypted)
+}
+
+/// Возвращает расшифрованный passphrase, сохраненный вместе с ключом (если он есть) -
+/// позволяет хосту ссылаться на управляемый ключ без хранения passphrase per-host
+pub fn resolve_key_passphrase(key_id: &str) -> Result<Option<ZeroizingString>, String> {
+    let keys = load_store()?;
+    let stored = keys.iter().find(|k| k.id == key_id).ok_or_else(|| format!("Ключ с id {} не найден", key_id))?;
+    match &stored.passphrase {
+        Some(encrypted) => Ok(Some(decrypt_password(encrypted)?)),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::VecDeque;
+
+/// Ограниченный по размеру кольцевой буфер строк вывода для одного хоста.
+/// Используется пакетным исполнителем, чтобы не накапливать неограниченный
+/// объем stdout/stderr в памяти при потоковом выполнении команд.
+#[derive(Debug, Clone)]
+pub struct LogBuffer {
+    buf: VecDeque<String>,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buf: VecDeque::with_capacity(capacity.min(1024)),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Добавляет строку в буфер, вытесняя самую старую при достижении capacity
+    pub fn push_line(&mut self, line: String) {
+        if self.buf.len() == self.capacity {
+            self.buf.pop_front();
+        }
+        self.buf.push_back(line);
+    }
+
+    /// Возвращает снимок всех строк в буфере (от старых к новым)
+    pub fn lines(&self) -> Vec<String> {
+        self.buf.iter().cloned().collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.buf.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_line_respects_capacity() {
+        let mut buf = LogBuffer::new(3);
+        buf.push_line("a".to_string());
+        buf.push_line("b".to_string());
+        buf.push_line("c".to_string());
+        buf.push_line("d".to_string());
+
+        assert_eq!(buf.lines(), vec!["b".to_string(), "c".to_string(), "d".to_string()]);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut buf = LogBuffer::new(5);
+        buf.push_line("a".to_string());
+        buf.clear();
+        assert!(buf.is_empty());
+    }
+}
@@ -10,6 +10,16 @@ mod error;
 mod excel_export;
 mod error_handler;
 mod command_validation;
+mod shell;
+mod log_buffer;
+mod tunnel;
+mod watch;
+mod keys;
+mod sftp;
+mod vault;
+mod ppk;
+mod age_vault;
+mod command_audit;
 
 use tauri::{Manager, SystemTray, SystemTrayEvent, SystemTrayMenu, CustomMenuItem, WindowEvent};
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -23,7 +33,7 @@ pub fn set_close_to_tray_setting(enabled: bool) {
 }
 
 fn main() {
-    env_logger::init();
+    audit::init_tracing();
     error_handler::setup_error_handling();
     
     let quit = CustomMenuItem::new("quit".to_string(), "Выход");
@@ -95,6 +105,10 @@ fn main() {
             commands::export_to_excel,
             commands::get_audit_logs,
             commands::clear_audit_logs,
+            commands::query_audit_logs,
+            commands::export_audit_timeseries,
+            commands::subscribe_audit_stream,
+            commands::unsubscribe_audit_stream,
             commands::test_ssh_connection,
             commands::save_temp_file,
             commands::save_file,
@@ -103,16 +117,63 @@ fn main() {
             commands::hash_settings_password,
             commands::verify_settings_password,
             commands::set_close_to_tray,
+            commands::open_shell_session,
+            commands::write_shell_input,
+            commands::resize_shell,
+            commands::close_shell_session,
+            commands::get_host_log_buffer,
+            commands::start_port_forward,
+            commands::stop_port_forward,
+            commands::list_port_forwards,
+            commands::export_batch_results,
+            commands::get_retry_schedule,
+            commands::watch_remote_path,
+            commands::unwatch_remote_path,
+            commands::create_ssh_key,
+            commands::list_ssh_keys,
+            commands::delete_ssh_key,
+            commands::import_ssh_key,
+            commands::reset_key_passphrase,
+            commands::unlock_encryption_with_master_password,
+            commands::start_encryption_key_rotation,
+            commands::re_encrypt_all_secrets,
+            commands::finish_encryption_key_rotation,
+            commands::export_recovery_code,
+            commands::import_recovery_code,
+            commands::verify_command_audit_log,
+            commands::vault_unlock,
+            commands::vault_save_config,
+            commands::vault_load_config,
+            commands::vault_list,
+            commands::vault_delete,
         ])
         .manage(pool.clone())
         .manage(commands::CancellationToken::new())
+        .manage(std::sync::Arc::new(shell::ShellSessionManager::new()))
+        .manage(std::sync::Arc::new(parking_lot::Mutex::new(std::collections::HashMap::<String, log_buffer::LogBuffer>::new())))
+        .manage(std::sync::Arc::new(tunnel::PortForwardManager::new()))
+        .manage(std::sync::Arc::new(parking_lot::Mutex::new(std::collections::HashMap::<u64, Vec<commands::RetryInfo>>::new())))
+        .manage(std::sync::Arc::new(watch::RemoteWatchManager::new()))
         .setup(move |app| {
             // Получаем app_data_dir для инициализации шифрования
             let app_data_dir = app.path_resolver().app_data_dir();
             
             // Инициализация шифрования (с сохранением ключа между сессиями)
-            security::init_encryption(app_data_dir);
-            
+            security::init_encryption(app_data_dir.clone());
+
+            // Инициализация at-rest шифрования для ключевого материала, который крейт
+            // вынужден класть на диск (сконвертированные PPK-ключи, хранилище управляемых ключей)
+            age_vault::init_age_vault(app_data_dir.clone());
+
+            // Инициализация защищенного от подделки журнала выполненных команд
+            // (отдельно от общего журнала аудита - своя хэш-цепочка и ротация)
+            command_audit::init_command_audit_log(app_data_dir.clone());
+
+            // Инициализация хранилища SSH-ключей
+            if let Some(dir) = app_data_dir {
+                keys::init_key_store(dir);
+            }
+
             // Инициализация аудита
             audit::init_audit_log(app.app_handle());
             
@@ -0,0 +1,192 @@
+use crate::error::{AppError, AppResult};
+use crate::ssh::SshConnection;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Размер блока для потокового чтения/записи - файлы не буферизуются целиком в память
+const CHUNK_SIZE: usize = 32 * 1024;
+
+/// Запись в удаленной директории, как ее возвращает `Sftp::list_dir`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteEntry {
+    pub name: String,
+    pub path: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub permissions: u32,
+}
+
+/// Обертка над `ssh2::Sftp`, использующая ту же аутентифицированную сессию,
+/// что и выполнение команд на этом соединении (`SshConnection::session`)
+pub struct Sftp<'a> {
+    connection: &'a SshConnection,
+}
+
+impl<'a> Sftp<'a> {
+    pub(crate) fn new(connection: &'a SshConnection) -> Self {
+        Self { connection }
+    }
+
+    fn open(&self) -> AppResult<ssh2::Sftp> {
+        self.connection
+            .raw_session()
+            .lock()
+            .sftp()
+            .map_err(|e| AppError::SshError(format!("Failed to open SFTP subsystem on {}: {}", self.connection.host(), e)))
+    }
+
+    /// Загружает локальный файл на удаленный хост, стримя блоками по `CHUNK_SIZE` байт.
+    /// `mode` - POSIX-права доступа удаленного файла (например, 0o644)
+    pub fn upload(
+        &self,
+        local: &Path,
+        remote: &Path,
+        mode: i32,
+        mut on_progress: Option<impl FnMut(u64, u64)>,
+    ) -> AppResult<()> {
+        let sftp = self.open()?;
+
+        let mut local_file = File::open(local)
+            .map_err(|e| AppError::FileError(format!("Failed to open local file {}: {}", local.display(), e)))?;
+        let total = local_file
+            .metadata()
+            .map(|m| m.len())
+            .unwrap_or(0);
+
+        let mut remote_file = sftp
+            .create(remote)
+            .map_err(|e| AppError::SshError(format!("Failed to create remote file {}: {}", remote.display(), e)))?;
+        if let Err(e) = remote_file.setstat(ssh2::FileStat {
+            size: None,
+            uid: None,
+            gid: None,
+            perm: Some(mode as u32),
+            atime: None,
+            mtime: None,
+        }) {
+            log::warn!("Failed to set permissions on {}: {}", remote.display(), e);
+        }
+
+        let mut buf = [0u8; CHUNK_SIZE];
+        let mut sent: u64 = 0;
+        loop {
+            let n = local_file
+                .read(&mut buf)
+                .map_err(|e| AppError::FileError(format!("Failed to read local file {}: {}", local.display(), e)))?;
+            if n == 0 {
+                break;
+            }
+            remote_file
+                .write_all(&buf[..n])
+                .map_err(|e| AppError::SshError(format!("Failed to write remote file {}: {}", remote.display(), e)))?;
+            sent += n as u64;
+            if let Some(cb) = on_progress.as_mut() {
+                cb(sent, total);
+            }
+        }
+
+        remote_file
+            .flush()
+            .map_err(|e| AppError::SshError(format!("Failed to flush remote file {}: {}", remote.display(), e)))
+    }
+
+    /// Скачивает удаленный файл, стримя блоками по `CHUNK_SIZE` байт
+    pub fn download(
+        &self,
+        remote: &Path,
+        local: &Path,
+        mut on_progress: Option<impl FnMut(u64, u64)>,
+    ) -> AppResult<()> {
+        let sftp = self.open()?;
+
+        let mut remote_file = sftp
+            .open(remote)
+            .map_err(|e| AppError::SshError(format!("Failed to open remote file {}: {}", remote.display(), e)))?;
+        let total = remote_file
+            .stat()
+            .map(|s| s.size.unwrap_or(0))
+            .unwrap_or(0);
+
+        let mut local_file = File::create(local)
+            .map_err(|e| AppError::FileError(format!("Failed to create local file {}: {}", local.display(), e)))?;
+
+        let mut buf = [0u8; CHUNK_SIZE];
+        let mut received: u64 = 0;
+        loop {
+            let n = remote_file
+                .read(&mut buf)
+                .map_err(|e| AppError::SshError(format!("Failed to read remote file {}: {}", remote.display(), e)))?;
+            if n == 0 {
+                break;
+            }
+            local_file
+                .write_all(&buf[..n])
+                .map_err(|e| AppError::FileError(format!("Failed to write local file {}: {}", local.display(), e)))?;
+            received += n as u64;
+            if let Some(cb) = on_progress.as_mut() {
+                cb(received, total);
+            }
+        }
+
+        local_file
+            .flush()
+            .map_err(|e| AppError::FileError(format!("Failed to flush local file {}: {}", local.display(), e)))
+    }
+
+    /// Список содержимого удаленной директории
+    pub fn list_dir(&self, remote: &Path) -> AppResult<Vec<RemoteEntry>> {
+        let sftp = self.open()?;
+        let entries = sftp
+            .readdir(remote)
+            .map_err(|e| AppError::SshError(format!("Failed to list remote directory {}: {}", remote.display(), e)))?;
+
+        Ok(entries
+            .into_iter()
+            .map(|(path, stat)| RemoteEntry {
+                name: path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                path: path.to_string_lossy().into_owned(),
+                is_dir: stat.is_dir(),
+                size: stat.size.unwrap_or(0),
+                permissions: stat.perm.unwrap_or(0),
+            })
+            .collect())
+    }
+
+    /// Возвращает метаданные удаленного файла или директории
+    pub fn stat(&self, remote: &Path) -> AppResult<RemoteEntry> {
+        let sftp = self.open()?;
+        let stat = sftp
+            .stat(remote)
+            .map_err(|e| AppError::SshError(format!("Failed to stat remote path {}: {}", remote.display(), e)))?;
+
+        Ok(RemoteEntry {
+            name: remote
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            path: remote.to_string_lossy().into_owned(),
+            is_dir: stat.is_dir(),
+            size: stat.size.unwrap_or(0),
+            permissions: stat.perm.unwrap_or(0),
+        })
+    }
+
+    /// Создает удаленную директорию с заданными правами доступа
+    pub fn mkdir(&self, remote: &Path, mode: i32) -> AppResult<()> {
+        let sftp = self.open()?;
+        sftp.mkdir(remote, mode)
+            .map_err(|e| AppError::SshError(format!("Failed to create remote directory {}: {}", remote.display(), e)))
+    }
+
+    /// Удаляет удаленный файл (для директорий используйте `rmdir` через список + рекурсию на вызывающей стороне)
+    pub fn remove(&self, remote: &Path) -> AppResult<()> {
+        let sftp = self.open()?;
+        sftp.unlink(remote)
+            .map_err(|e| AppError::SshError(format!("Failed to remove remote file {}: {}", remote.display(), e)))
+    }
+}
@@ -0,0 +1,214 @@
+use crate::error::{AppError, AppResult};
+use crate::ssh::PooledConnection;
+use parking_lot::Mutex;
+use ssh2::Channel;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::Arc;
+use tauri::Window;
+use uuid::Uuid;
+
+/// Размер псевдотерминала (PTY) для интерактивной сессии
+#[derive(Debug, Clone, Copy)]
+pub struct PtySize {
+    pub rows: u32,
+    pub cols: u32,
+}
+
+impl Default for PtySize {
+    fn default() -> Self {
+        Self { rows: 24, cols: 80 }
+    }
+}
+
+/// Интерактивная shell-сессия поверх SSH-канала с запрошенным PTY
+pub struct ShellSession {
+    channel: Arc<Mutex<Channel>>,
+    host: String,
+    // Держит соединение живым и вне пула простоев на все время жизни shell-сессии: канал
+    // держит сырую ссылку на сессию, а пул не должен выдавать ее кому-то еще, пока по ней
+    // работает интерактивный shell. Освобождается обратно в пул (или закрывается), когда
+    // `ShellSession` сама удаляется. Также используется `read_available` для переключения
+    // сессии в неблокирующий режим на время чтения.
+    connection: PooledConnection,
+}
+
+impl ShellSession {
+    /// Открывает PTY и запускает shell на удаленном хосте
+    pub fn open(connection: PooledConnection, size: PtySize) -> AppResult<Self> {
+        let mut channel = connection
+            .raw_session()
+            .lock()
+            .channel_session()
+            .map_err(|e| AppError::SshError(format!("Failed to create channel: {}", e)))?;
+
+        channel
+            .request_pty("xterm", None, Some((size.cols, size.rows, 0, 0)))
+            .map_err(|e| AppError::SshError(format!("Failed to request PTY: {}", e)))?;
+
+        channel
+            .shell()
+            .map_err(|e| AppError::SshError(format!("Failed to start shell: {}", e)))?;
+
+        channel.handle_extended_data(ssh2::ExtendedData::Merge)
+            .map_err(|e| AppError::SshError(format!("Failed to merge extended data: {}", e)))?;
+
+        Ok(Self {
+            host: connection.host().to_string(),
+            channel: Arc::new(Mutex::new(channel)),
+            connection,
+        })
+    }
+
+    pub fn write_input(&self, data: &[u8]) -> AppResult<()> {
+        let mut channel = self.channel.lock();
+        channel
+            .write_all(data)
+            .map_err(|e| AppError::SshError(format!("Failed to write to shell on {}: {}", self.host, e)))?;
+        channel
+            .flush()
+            .map_err(|e| AppError::SshError(format!("Failed to flush shell input on {}: {}", self.host, e)))
+    }
+
+    pub fn resize(&self, cols: u32, rows: u32) -> AppResult<()> {
+        let mut channel = self.channel.lock();
+        channel
+            .request_pty_size(cols, rows, None, None)
+            .map_err(|e| AppError::SshError(format!("Failed to resize PTY on {}: {}", self.host, e)))
+    }
+
+    /// Читает доступные данные без блокировки, возвращает пустой вектор если данных нет.
+    /// Как и `SshConnection::execute_command_streaming`, временно переводит сессию в
+    /// неблокирующий режим на время чтения и трактует `WouldBlock` как "данных пока нет" -
+    /// без этого `channel.read` блокируется в штатном блокирующем режиме сессии, как только
+    /// удаленная сторона затихает (например, простаивает на приглашении shell), и зависает
+    /// навсегда, удерживая `self.channel.lock()` - а значит и все остальные методы,
+    /// которым нужен тот же мьютекс (`write_input`, `resize`, `close`), тоже блокируются.
+    pub fn read_available(&self) -> Vec<u8> {
+        self.connection.raw_session().lock().set_blocking(false);
+
+        let mut channel = self.channel.lock();
+        let mut buf = [0u8; 4096];
+        let mut out = Vec::new();
+        loop {
+            match channel.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => out.extend_from_slice(&buf[..n]),
+                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(_) => break,
+            }
+        }
+        drop(channel);
+
+        self.connection.raw_session().lock().set_blocking(true);
+        out
+    }
+
+    pub fn is_eof(&self) -> bool {
+        self.channel.lock().eof()
+    }
+
+    pub fn close(&self) {
+        let mut channel = self.channel.lock();
+        let _ = channel.send_eof();
+        let _ = channel.close();
+        let _ = channel.wait_close();
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+struct ShellOutputEvent {
+    session_id: String,
+    data: Vec<u8>,
+}
+
+/// Реестр активных shell-сессий, управляемый через tauri::State
+#[derive(Default)]
+pub struct ShellSessionManager {
+    sessions: Mutex<HashMap<String, Arc<ShellSession>>>,
+}
+
+impl ShellSessionManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Открывает новую сессию и запускает фоновый поток, стримящий вывод в окно.
+    /// `is_cancelled` - callback на основе существующего `CancellationToken`, позволяющий
+    /// корректно завершить сессию, если пользователь отменил выполнение из общего UI
+    pub fn open_session<F>(
+        &self,
+        connection: PooledConnection,
+        size: PtySize,
+        window: Window,
+        is_cancelled: F,
+    ) -> AppResult<String>
+    where
+        F: Fn() -> bool + Send + 'static,
+    {
+        let session = Arc::new(ShellSession::open(connection, size)?);
+        let session_id = Uuid::new_v4().to_string();
+
+        self.sessions.lock().insert(session_id.clone(), session.clone());
+
+        let reader_session = session.clone();
+        let reader_id = session_id.clone();
+        std::thread::spawn(move || {
+            loop {
+                if reader_session.is_eof() || is_cancelled() {
+                    break;
+                }
+                let chunk = reader_session.read_available();
+                if !chunk.is_empty() {
+                    let _ = window.emit(
+                        "shell-output",
+                        ShellOutputEvent {
+                            session_id: reader_id.clone(),
+                            data: chunk,
+                        },
+                    );
+                } else {
+                    std::thread::sleep(std::time::Duration::from_millis(30));
+                }
+            }
+            reader_session.close();
+        });
+
+        Ok(session_id)
+    }
+
+    /// Закрывает все активные shell-сессии (например, при глобальной отмене выполнения)
+    pub fn close_all(&self) {
+        let mut sessions = self.sessions.lock();
+        for (_, session) in sessions.drain() {
+            session.close();
+        }
+    }
+
+    pub fn write_input(&self, session_id: &str, data: &[u8]) -> AppResult<()> {
+        let sessions = self.sessions.lock();
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| AppError::SshError(format!("Shell session not found: {}", session_id)))?;
+        session.write_input(data)
+    }
+
+    pub fn resize(&self, session_id: &str, cols: u32, rows: u32) -> AppResult<()> {
+        let sessions = self.sessions.lock();
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| AppError::SshError(format!("Shell session not found: {}", session_id)))?;
+        session.resize(cols, rows)
+    }
+
+    pub fn close_session(&self, session_id: &str) -> AppResult<()> {
+        let session = self.sessions.lock().remove(session_id);
+        match session {
+            Some(session) => {
+                session.close();
+                Ok(())
+            }
+            None => Err(AppError::SshError(format!("Shell session not found: {}", session_id))),
+        }
+    }
+}
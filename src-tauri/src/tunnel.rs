@@ -0,0 +1,354 @@
+use crate::error::{AppError, AppResult};
+use crate::ssh::{SshConfig, SshConnectionPool};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::Window;
+
+/// Направление проброса порта
+/// Как часто форвард-циклы проверяют живость SSH-соединения через keepalive-пробу,
+/// пока туннель простаивает без активных подключений - иначе мертвое соединение
+/// (например, оборвавшееся по таймауту NAT) замечается только при следующей попытке
+/// открыть канал, а простаивающий туннель может не делать таких попыток бесконечно долго.
+const LIVENESS_PROBE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ForwardDirection {
+    Local,
+    Remote,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortForwardConfig {
+    pub config: SshConfig,
+    pub direction: ForwardDirection,
+    pub local_host: String,
+    pub local_port: u16,
+    pub remote_host: String,
+    pub remote_port: u16,
+}
+
+impl PortForwardConfig {
+    fn bind_key(&self) -> String {
+        format!("{}:{}", self.local_host, self.local_port)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TunnelStatus {
+    Up,
+    Down,
+    Respawning,
+}
+
+impl Default for TunnelStatus {
+    fn default() -> Self {
+        TunnelStatus::Down
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct TunnelStatusEvent {
+    bind_key: String,
+    status: TunnelStatus,
+    detail: Option<String>,
+}
+
+struct ActiveForward {
+    config: PortForwardConfig,
+    stop_flag: Arc<AtomicBool>,
+    status: Arc<Mutex<TunnelStatus>>,
+}
+
+/// Реестр активных проброшенных портов, управляемый через tauri::State.
+/// Каждый форвард имеет фоновую "checker"-задачу, которая периодически
+/// проверяет живость туннеля и пересоздает его при обрыве.
+#[derive(Default)]
+pub struct PortForwardManager {
+    forwards: Mutex<HashMap<String, ActiveForward>>,
+}
+
+impl PortForwardManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start(
+        &self,
+        cfg: PortForwardConfig,
+        pool: Arc<SshConnectionPool>,
+        window: Window,
+    ) -> AppResult<String> {
+        let bind_key = cfg.bind_key();
+
+        // Если на этом локальном адресе уже есть форвард - сначала останавливаем его
+        self.stop(&bind_key);
+
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let status = Arc::new(Mutex::new(TunnelStatus::Down));
+
+        self.forwards.lock().insert(
+            bind_key.clone(),
+            ActiveForward {
+                config: cfg.clone(),
+                stop_flag: stop_flag.clone(),
+                status: status.clone(),
+            },
+        );
+
+        spawn_forward_loop(bind_key.clone(), cfg, pool, stop_flag, status, window);
+
+        Ok(bind_key)
+    }
+
+    pub fn stop(&self, bind_key: &str) -> bool {
+        if let Some(forward) = self.forwards.lock().remove(bind_key) {
+            forward.stop_flag.store(true, Ordering::SeqCst);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn list(&self) -> Vec<(PortForwardConfig, TunnelStatus)> {
+        self.forwards
+            .lock()
+            .values()
+            .map(|f| (f.config.clone(), f.status.lock().clone()))
+            .collect()
+    }
+}
+
+fn emit_status(window: &Window, bind_key: &str, status: &Arc<Mutex<TunnelStatus>>, new_status: TunnelStatus, detail: Option<String>) {
+    *status.lock() = new_status.clone();
+    let _ = window.emit(
+        "tunnel-status",
+        TunnelStatusEvent {
+            bind_key: bind_key.to_string(),
+            status: new_status,
+            detail,
+        },
+    );
+}
+
+/// Основной цикл форварда: поднимает туннель, держит его живым через периодическую
+/// проверку, и пересоздает при обрыве соединения
+fn spawn_forward_loop(
+    bind_key: String,
+    cfg: PortForwardConfig,
+    pool: Arc<SshConnectionPool>,
+    stop_flag: Arc<AtomicBool>,
+    status: Arc<Mutex<TunnelStatus>>,
+    window: Window,
+) {
+    std::thread::spawn(move || {
+        while !stop_flag.load(Ordering::SeqCst) {
+            emit_status(&window, &bind_key, &status, TunnelStatus::Respawning, None);
+
+            let connection = match pool.get_or_create(cfg.config.clone()) {
+                Ok(c) => c,
+                Err(e) => {
+                    emit_status(&window, &bind_key, &status, TunnelStatus::Down, Some(e.to_string()));
+                    if interruptible_wait(&stop_flag, std::time::Duration::from_secs(5)) {
+                        break;
+                    }
+                    continue;
+                }
+            };
+
+            match cfg.direction {
+                ForwardDirection::Local => {
+                    if let Err(e) = run_local_forward(&cfg, &connection, &stop_flag, &window, &bind_key, &status) {
+                        log::warn!("[Tunnel] Форвард {} упал: {}", bind_key, e);
+                    }
+                }
+                ForwardDirection::Remote => {
+                    if let Err(e) = run_remote_forward(&cfg, &connection, &stop_flag, &window, &bind_key, &status) {
+                        log::warn!("[Tunnel] Обратный форвард {} упал: {}", bind_key, e);
+                    }
+                }
+            }
+
+            if stop_flag.load(Ordering::SeqCst) {
+                break;
+            }
+            emit_status(&window, &bind_key, &status, TunnelStatus::Down, Some("Соединение разорвано, переподключение".to_string()));
+            if interruptible_wait(&stop_flag, std::time::Duration::from_secs(2)) {
+                break;
+            }
+        }
+        emit_status(&window, &bind_key, &status, TunnelStatus::Down, Some("Остановлено".to_string()));
+    });
+}
+
+fn interruptible_wait(stop_flag: &Arc<AtomicBool>, duration: std::time::Duration) -> bool {
+    let step = std::time::Duration::from_millis(100);
+    let mut remaining = duration;
+    while remaining > std::time::Duration::ZERO {
+        if stop_flag.load(Ordering::SeqCst) {
+            return true;
+        }
+        let sleep_time = remaining.min(step);
+        std::thread::sleep(sleep_time);
+        remaining = remaining.saturating_sub(sleep_time);
+    }
+    false
+}
+
+/// Локальный форвард: слушаем локальный порт, для каждого подключения открываем
+/// direct-tcpip канал на удаленный host:port и перекачиваем байты в обе стороны
+fn run_local_forward(
+    cfg: &PortForwardConfig,
+    connection: &crate::ssh::SshConnection,
+    stop_flag: &Arc<AtomicBool>,
+    window: &Window,
+    bind_key: &str,
+    status: &Arc<Mutex<TunnelStatus>>,
+) -> AppResult<()> {
+    let listener = TcpListener::bind((cfg.local_host.as_str(), cfg.local_port))
+        .map_err(|e| AppError::ConnectionError(format!("Failed to bind {}:{}: {}", cfg.local_host, cfg.local_port, e)))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| AppError::ConnectionError(format!("Failed to set non-blocking listener: {}", e)))?;
+
+    emit_status(window, bind_key, status, TunnelStatus::Up, None);
+
+    let mut last_probe = std::time::Instant::now();
+
+    while !stop_flag.load(Ordering::SeqCst) {
+        if last_probe.elapsed() >= LIVENESS_PROBE_INTERVAL {
+            if !connection.is_alive() {
+                return Err(AppError::ConnectionError("SSH-соединение не отвечает на keepalive".to_string()));
+            }
+            last_probe = std::time::Instant::now();
+        }
+
+        match listener.accept() {
+            Ok((local_stream, _addr)) => {
+                let session = connection.raw_session().clone();
+                let remote_host = cfg.remote_host.clone();
+                let remote_port = cfg.remote_port;
+                let bind_key = bind_key.to_string();
+                std::thread::spawn(move || {
+                    match session.lock().channel_direct_tcpip(&remote_host, remote_port, None) {
+                        Ok(channel) => pump_streams(local_stream, channel),
+                        Err(e) => log::warn!(
+                            "[Tunnel] {}: не удалось открыть direct-tcpip канал на {}:{}: {}",
+                            bind_key, remote_host, remote_port, e
+                        ),
+                    }
+                });
+            }
+            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+            }
+            Err(e) => {
+                return Err(AppError::ConnectionError(format!("Accept failed: {}", e)));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Обратный форвард: запрашиваем у сервера forward_listen на remote-порту и
+/// перекачиваем входящие каналы к локальному host:port
+fn run_remote_forward(
+    cfg: &PortForwardConfig,
+    connection: &crate::ssh::SshConnection,
+    stop_flag: &Arc<AtomicBool>,
+    window: &Window,
+    bind_key: &str,
+    status: &Arc<Mutex<TunnelStatus>>,
+) -> AppResult<()> {
+    let (mut listener, _bound_port) = connection
+        .raw_session()
+        .lock()
+        .channel_forward_listen(cfg.remote_port, Some(&cfg.remote_host), None)
+        .map_err(|e| AppError::SshError(format!("Failed to listen on remote port {}: {}", cfg.remote_port, e)))?;
+
+    emit_status(window, bind_key, status, TunnelStatus::Up, None);
+
+    let mut last_probe = std::time::Instant::now();
+
+    while !stop_flag.load(Ordering::SeqCst) {
+        if last_probe.elapsed() >= LIVENESS_PROBE_INTERVAL {
+            if !connection.is_alive() {
+                return Err(AppError::ConnectionError("SSH-соединение не отвечает на keepalive".to_string()));
+            }
+            last_probe = std::time::Instant::now();
+        }
+
+        match listener.accept() {
+            Ok(channel) => {
+                if let Ok(local_stream) = TcpStream::connect((cfg.local_host.as_str(), cfg.local_port)) {
+                    pump_streams(local_stream, channel);
+                }
+            }
+            Err(_) => {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Перекачивает данные между локальным TCP-сокетом и SSH-каналом в обоих направлениях.
+/// Оба конца переводятся в неблокирующий режим и опрашиваются в одном потоке,
+/// так как ssh2::Channel нельзя безопасно читать/писать из двух потоков одновременно.
+fn pump_streams(mut local_stream: TcpStream, mut channel: ssh2::Channel) {
+    if local_stream.set_nonblocking(true).is_err() {
+        return;
+    }
+
+    let mut local_buf = [0u8; 8192];
+    let mut remote_buf = [0u8; 8192];
+
+    loop {
+        let mut made_progress = false;
+
+        match local_stream.read(&mut local_buf) {
+            Ok(0) => break,
+            Ok(n) => {
+                if channel.write_all(&local_buf[..n]).is_err() {
+                    break;
+                }
+                made_progress = true;
+            }
+            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+            Err(_) => break,
+        }
+
+        match channel.read(&mut remote_buf) {
+            Ok(0) => {
+                if channel.eof() {
+                    break;
+                }
+            }
+            Ok(n) => {
+                if local_stream.write_all(&remote_buf[..n]).is_err() {
+                    break;
+                }
+                made_progress = true;
+            }
+            Err(_) => {}
+        }
+
+        if channel.eof() {
+            break;
+        }
+
+        if !made_progress {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
+
+    let _ = channel.send_eof();
+    let _ = channel.close();
+}
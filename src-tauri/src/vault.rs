@@ -0,0 +1,421 @@
+use crate::error::{AppError, AppResult};
+use crate::security::{decrypt_password, encrypt_password};
+use crate::ssh::{AuthMethod, SshConfig};
+use argon2::{Algorithm, Argon2, ParamsBuilder, Version};
+use chacha20poly1305::{aead::Aead, KeyInit, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::{Row, SqlitePool};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use uuid::Uuid;
+
+/// Параметры Argon2id для деривации ключа хранилища из мастер-пароля пользователя.
+/// Значения соответствуют рекомендациям OWASP для интерактивного хеширования.
+const ARGON2_MEM_COST_KIB: u32 = 19_456;
+const ARGON2_TIME_COST: u32 = 2;
+const ARGON2_PARALLELISM: u32 = 1;
+const VAULT_KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+
+static VAULT_POOL: OnceLock<SqlitePool> = OnceLock::new();
+static VAULT_KEY: Mutex<Option<[u8; VAULT_KEY_LEN]>> = Mutex::new(None);
+
+/// Запись хранилища без секретов - безопасна для отображения в списке сохраненных хостов
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultEntry {
+    pub id: String,
+    pub label: String,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub auth_type: String,
+}
+
+/// То, что на самом деле нужно зашифровать для каждого метода аутентификации -
+/// расшифрованные секреты, временно извлеченные из `AuthMethod` перед сохранением в БД.
+/// Хранится только в зашифрованном виде (XChaCha20-Poly1305) в таблице `secrets`.
+#[derive(Debug, Serialize, Deserialize)]
+enum SecretPayload {
+    Password { password: String },
+    PrivateKey { key_path: String, passphrase: Option<String> },
+    PuttyKey { ppk_path: String, passphrase: Option<String> },
+    Agent { preferred_comment: Option<String> },
+    ManagedKey { key_id: String, passphrase: Option<String> },
+    KeyboardInteractive {
+        answer: Option<String>,
+        prompt_answers: Option<std::collections::HashMap<String, String>>,
+    },
+}
+
+fn auth_type_str(auth: &AuthMethod) -> &'static str {
+    match auth {
+        AuthMethod::Password(_) => "password",
+        AuthMethod::PrivateKey { .. } => "key",
+        AuthMethod::PuttyKey { .. } => "ppk",
+        AuthMethod::Agent { .. } => "agent",
+        AuthMethod::ManagedKey { .. } => "managed_key",
+        AuthMethod::KeyboardInteractive { .. } => "keyboard_interactive",
+    }
+}
+
+fn to_secret_payload(auth: &AuthMethod) -> AppResult<SecretPayload> {
+    Ok(match auth {
+        AuthMethod::Password(encrypted) => SecretPayload::Password {
+            password: decrypt_password(encrypted)
+                .map_err(|e| AppError::VaultError(format!("Failed to decrypt password for vault storage: {}", e)))?
+                .as_str()
+                .to_string(),
+        },
+        AuthMethod::PrivateKey { key_path, passphrase } => SecretPayload::PrivateKey {
+            key_path: key_path.clone(),
+            passphrase: decrypt_optional_passphrase(passphrase)?,
+        },
+        AuthMethod::PuttyKey { ppk_path, passphrase } => SecretPayload::PuttyKey {
+            ppk_path: ppk_path.clone(),
+            passphrase: decrypt_optional_passphrase(passphrase)?,
+        },
+        AuthMethod::Agent { preferred_comment } => SecretPayload::Agent {
+            preferred_comment: preferred_comment.clone(),
+        },
+        AuthMethod::ManagedKey { key_id, passphrase } => SecretPayload::ManagedKey {
+            key_id: key_id.clone(),
+            passphrase: decrypt_optional_passphrase(passphrase)?,
+        },
+        AuthMethod::KeyboardInteractive { answer, prompt_answers } => SecretPayload::KeyboardInteractive {
+            answer: decrypt_optional_passphrase(answer)?,
+            prompt_answers: prompt_answers
+                .as_ref()
+                .map(|map| {
+                    map.iter()
+                        .map(|(prompt_substring, encrypted)| {
+                            decrypt_password(encrypted)
+                                .map(|z| (prompt_substring.clone(), z.as_str().to_string()))
+                                .map_err(|e| {
+                                    AppError::VaultError(format!(
+                                        "Failed to decrypt keyboard-interactive answer for vault storage: {}",
+                                        e
+                                    ))
+                                })
+                        })
+                        .collect::<AppResult<std::collections::HashMap<_, _>>>()
+                })
+                .transpose()?,
+        },
+    })
+}
+
+fn decrypt_optional_passphrase(passphrase: &Option<crate::security::EncryptedData>) -> AppResult<Option<String>> {
+    passphrase
+        .as_ref()
+        .map(|p| {
+            decrypt_password(p)
+                .map(|z| z.as_str().to_string())
+                .map_err(|e| AppError::VaultError(format!("Failed to decrypt passphrase for vault storage: {}", e)))
+        })
+        .transpose()
+}
+
+fn encrypt_optional_passphrase(passphrase: Option<String>) -> Option<crate::security::EncryptedData> {
+    passphrase.map(|p| {
+        encrypt_password(&p).unwrap_or_else(|e| {
+            log::error!("Failed to re-encrypt passphrase loaded from vault: {}", e);
+            crate::security::EncryptedData::empty()
+        })
+    })
+}
+
+fn from_secret_payload(payload: SecretPayload) -> AuthMethod {
+    match payload {
+        SecretPayload::Password { password } => AuthMethod::Password(
+            encrypt_password(&password).unwrap_or_else(|e| {
+                log::error!("Failed to re-encrypt password loaded from vault: {}", e);
+                crate::security::EncryptedData::empty()
+            }),
+        ),
+        SecretPayload::PrivateKey { key_path, passphrase } => AuthMethod::PrivateKey {
+            key_path,
+            passphrase: encrypt_optional_passphrase(passphrase),
+        },
+        SecretPayload::PuttyKey { ppk_path, passphrase } => AuthMethod::PuttyKey {
+            ppk_path,
+            passphrase: encrypt_optional_passphrase(passphrase),
+        },
+        SecretPayload::Agent { preferred_comment } => AuthMethod::Agent { preferred_comment },
+        SecretPayload::ManagedKey { key_id, passphrase } => AuthMethod::ManagedKey {
+            key_id,
+            passphrase: encrypt_optional_passphrase(passphrase),
+        },
+        SecretPayload::KeyboardInteractive { answer, prompt_answers } => AuthMethod::KeyboardInteractive {
+            answer: encrypt_optional_passphrase(answer),
+            prompt_answers: prompt_answers.map(|map| {
+                map.into_iter()
+                    .map(|(prompt_substring, value)| {
+                        let encrypted = encrypt_password(&value).unwrap_or_else(|e| {
+                            log::error!("Failed to re-encrypt keyboard-interactive answer loaded from vault: {}", e);
+                            crate::security::EncryptedData::empty()
+                        });
+                        (prompt_substring, encrypted)
+                    })
+                    .collect()
+            }),
+        },
+    }
+}
+
+fn load_or_create_salt(app_data_dir: &std::path::Path) -> AppResult<[u8; SALT_LEN]> {
+    let salt_path = app_data_dir.join("vault_salt");
+    if let Ok(bytes) = std::fs::read(&salt_path) {
+        if bytes.len() == SALT_LEN {
+            let mut salt = [0u8; SALT_LEN];
+            salt.copy_from_slice(&bytes);
+            return Ok(salt);
+        }
+        log::warn!("Vault salt file has unexpected size, regenerating");
+    }
+
+    let mut salt = [0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    std::fs::create_dir_all(app_data_dir)
+        .map_err(|e| AppError::VaultError(format!("Failed to create vault directory: {}", e)))?;
+    std::fs::write(&salt_path, salt)
+        .map_err(|e| AppError::VaultError(format!("Failed to write vault salt: {}", e)))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(metadata) = std::fs::metadata(&salt_path) {
+            let mut perms = metadata.permissions();
+            perms.set_mode(0o600);
+            let _ = std::fs::set_permissions(&salt_path, perms);
+        }
+    }
+
+    Ok(salt)
+}
+
+fn derive_vault_key(master_password: &str, salt: &[u8; SALT_LEN]) -> AppResult<[u8; VAULT_KEY_LEN]> {
+    let params = ParamsBuilder::new()
+        .m_cost(ARGON2_MEM_COST_KIB)
+        .t_cost(ARGON2_TIME_COST)
+        .p_cost(ARGON2_PARALLELISM)
+        .output_len(VAULT_KEY_LEN)
+        .build()
+        .map_err(|e| AppError::VaultError(format!("Invalid Argon2id parameters: {}", e)))?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+    let mut key = [0u8; VAULT_KEY_LEN];
+    argon2
+        .hash_password_into(master_password.as_bytes(), salt, &mut key)
+        .map_err(|e| AppError::VaultError(format!("Failed to derive vault key: {}", e)))?;
+    Ok(key)
+}
+
+fn vault_key() -> AppResult<[u8; VAULT_KEY_LEN]> {
+    VAULT_KEY
+        .lock()
+        .map_err(|e| AppError::VaultError(format!("Vault key mutex poisoned: {}", e)))?
+        .ok_or_else(|| AppError::VaultError("Vault is locked - call init_vault with the master password first".to_string()))
+}
+
+fn pool() -> AppResult<&'static SqlitePool> {
+    VAULT_POOL
+        .get()
+        .ok_or_else(|| AppError::VaultError("Vault database is not initialized".to_string()))
+}
+
+fn encrypt_secret(plaintext: &[u8]) -> AppResult<(Vec<u8>, Vec<u8>)> {
+    let key = vault_key()?;
+    let cipher = XChaCha20Poly1305::new((&key).into());
+
+    let mut nonce_bytes = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+    let nonce = XNonce::from_slice(&nonce_bytes);
+
+    let ciphertext = cipher
+        .encrypt(nonce, plaintext)
+        .map_err(|e| AppError::VaultError(format!("Failed to encrypt secret: {}", e)))?;
+
+    Ok((nonce_bytes.to_vec(), ciphertext))
+}
+
+fn decrypt_secret(nonce: &[u8], ciphertext: &[u8]) -> AppResult<Vec<u8>> {
+    let key = vault_key()?;
+    let cipher = XChaCha20Poly1305::new((&key).into());
+    let nonce = XNonce::from_slice(nonce);
+
+    cipher
+        .decrypt(nonce, ciphertext)
+        .map_err(|e| AppError::VaultError(format!("Failed to decrypt secret: {}", e)))
+}
+
+/// Открывает (или создает) зашифрованное хранилище учетных данных `vault.db` в `app_data_dir`.
+/// Ключ хранилища выводится из `master_password` через Argon2id и живет только в памяти -
+/// без повторного вызова этой функции с правильным паролем таблица `secrets` нечитаема.
+pub async fn init_vault(app_data_dir: PathBuf, master_password: &str) -> AppResult<()> {
+    let salt = load_or_create_salt(&app_data_dir)?;
+    let key = derive_vault_key(master_password, &salt)?;
+    *VAULT_KEY
+        .lock()
+        .map_err(|e| AppError::VaultError(format!("Vault key mutex poisoned: {}", e)))? = Some(key);
+
+    let db_path = app_data_dir.join("vault.db");
+    let connect_url = format!("sqlite://{}?mode=rwc", db_path.display());
+
+    let db_pool = SqlitePoolOptions::new()
+        .max_connections(5)
+        .connect(&connect_url)
+        .await
+        .map_err(|e| AppError::VaultError(format!("Failed to open vault database: {}", e)))?;
+
+    sqlx::query("PRAGMA foreign_keys = ON;")
+        .execute(&db_pool)
+        .await
+        .map_err(|e| AppError::VaultError(format!("Failed to enable foreign keys: {}", e)))?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS credentials (
+            id TEXT PRIMARY KEY,
+            label TEXT NOT NULL,
+            host TEXT NOT NULL,
+            port INTEGER NOT NULL,
+            username TEXT NOT NULL,
+            auth_type TEXT NOT NULL
+        )",
+    )
+    .execute(&db_pool)
+    .await
+    .map_err(|e| AppError::VaultError(format!("Failed to create credentials table: {}", e)))?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS secrets (
+            id TEXT PRIMARY KEY,
+            nonce BLOB NOT NULL,
+            ciphertext BLOB NOT NULL,
+            FOREIGN KEY (id) REFERENCES credentials(id) ON DELETE CASCADE
+        )",
+    )
+    .execute(&db_pool)
+    .await
+    .map_err(|e| AppError::VaultError(format!("Failed to create secrets table: {}", e)))?;
+
+    VAULT_POOL
+        .set(db_pool)
+        .map_err(|_| AppError::VaultError("Vault database already initialized".to_string()))?;
+
+    Ok(())
+}
+
+/// Сохраняет конфигурацию подключения в хранилище под указанной меткой, возвращает id записи
+pub async fn save_config(config: &SshConfig, label: &str) -> AppResult<String> {
+    let payload = to_secret_payload(&config.auth_method)?;
+    let plaintext = serde_json::to_vec(&payload)
+        .map_err(|e| AppError::VaultError(format!("Failed to serialize secret payload: {}", e)))?;
+    let (nonce, ciphertext) = encrypt_secret(&plaintext)?;
+
+    let id = Uuid::new_v4().to_string();
+    let db_pool = pool()?;
+
+    sqlx::query("INSERT INTO credentials (id, label, host, port, username, auth_type) VALUES (?, ?, ?, ?, ?, ?)")
+        .bind(&id)
+        .bind(label)
+        .bind(&config.host)
+        .bind(config.port as i64)
+        .bind(&config.username)
+        .bind(auth_type_str(&config.auth_method))
+        .execute(db_pool)
+        .await
+        .map_err(|e| AppError::VaultError(format!("Failed to insert credential record: {}", e)))?;
+
+    sqlx::query("INSERT INTO secrets (id, nonce, ciphertext) VALUES (?, ?, ?)")
+        .bind(&id)
+        .bind(nonce)
+        .bind(ciphertext)
+        .execute(db_pool)
+        .await
+        .map_err(|e| AppError::VaultError(format!("Failed to insert secret record: {}", e)))?;
+
+    Ok(id)
+}
+
+/// Загружает и расшифровывает сохраненную конфигурацию подключения по id
+pub async fn load_config(id: &str) -> AppResult<SshConfig> {
+    let db_pool = pool()?;
+
+    let cred_row = sqlx::query("SELECT host, port, username FROM credentials WHERE id = ?")
+        .bind(id)
+        .fetch_optional(db_pool)
+        .await
+        .map_err(|e| AppError::VaultError(format!("Failed to read credential record: {}", e)))?
+        .ok_or_else(|| AppError::VaultError(format!("No saved connection with id {}", id)))?;
+
+    let secret_row = sqlx::query("SELECT nonce, ciphertext FROM secrets WHERE id = ?")
+        .bind(id)
+        .fetch_optional(db_pool)
+        .await
+        .map_err(|e| AppError::VaultError(format!("Failed to read secret record: {}", e)))?
+        .ok_or_else(|| AppError::VaultError(format!("No secret stored for connection {}", id)))?;
+
+    let nonce: Vec<u8> = secret_row.try_get("nonce").map_err(|e| AppError::VaultError(e.to_string()))?;
+    let ciphertext: Vec<u8> = secret_row.try_get("ciphertext").map_err(|e| AppError::VaultError(e.to_string()))?;
+    let plaintext = decrypt_secret(&nonce, &ciphertext)?;
+    let payload: SecretPayload = serde_json::from_slice(&plaintext)
+        .map_err(|e| AppError::VaultError(format!("Failed to parse decrypted secret payload: {}", e)))?;
+
+    let host: String = cred_row.try_get("host").map_err(|e| AppError::VaultError(e.to_string()))?;
+    let port: i64 = cred_row.try_get("port").map_err(|e| AppError::VaultError(e.to_string()))?;
+    let username: String = cred_row.try_get("username").map_err(|e| AppError::VaultError(e.to_string()))?;
+
+    Ok(SshConfig {
+        host,
+        port: port as u16,
+        username,
+        auth_method: from_secret_payload(payload),
+        timeout: 30,
+        keep_alive_interval: None,
+        reconnect_attempts: None,
+        reconnect_delay_base: None,
+        compression_enabled: None,
+        compression_level: None,
+        host_key_algorithms: None,
+        kex_algorithms: None,
+        ciphers: None,
+        mac_algorithms: None,
+        json_field_extractors: None,
+    })
+}
+
+/// Список сохраненных подключений без секретов - для отображения в UI
+pub async fn list() -> AppResult<Vec<VaultEntry>> {
+    let db_pool = pool()?;
+    let rows = sqlx::query("SELECT id, label, host, port, username, auth_type FROM credentials ORDER BY label ASC")
+        .fetch_all(db_pool)
+        .await
+        .map_err(|e| AppError::VaultError(format!("Failed to list saved connections: {}", e)))?;
+
+    rows.into_iter()
+        .map(|row| {
+            let port: i64 = row.try_get("port").map_err(|e| AppError::VaultError(e.to_string()))?;
+            Ok(VaultEntry {
+                id: row.try_get("id").map_err(|e| AppError::VaultError(e.to_string()))?,
+                label: row.try_get("label").map_err(|e| AppError::VaultError(e.to_string()))?,
+                host: row.try_get("host").map_err(|e| AppError::VaultError(e.to_string()))?,
+                port: port as u16,
+                username: row.try_get("username").map_err(|e| AppError::VaultError(e.to_string()))?,
+                auth_type: row.try_get("auth_type").map_err(|e| AppError::VaultError(e.to_string()))?,
+            })
+        })
+        .collect()
+}
+
+/// Удаляет сохраненное подключение - строка в `secrets` удаляется каскадно через FK
+pub async fn delete(id: &str) -> AppResult<()> {
+    let db_pool = pool()?;
+    sqlx::query("DELETE FROM credentials WHERE id = ?")
+        .bind(id)
+        .execute(db_pool)
+        .await
+        .map_err(|e| AppError::VaultError(format!("Failed to delete saved connection {}: {}", id, e)))?;
+    Ok(())
+}
@@ -0,0 +1,201 @@
+use crate::ssh::{SshConfig, SshConnectionPool, SshFamily};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::Window;
+use uuid::Uuid;
+
+/// Тип изменения удаленного пути, обнаруженный наблюдателем
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RemoteChangeKind {
+    Created,
+    Modified,
+    Removed,
+}
+
+#[derive(Clone, Serialize)]
+struct RemoteChangeEvent {
+    watch_id: String,
+    host: String,
+    path: String,
+    kind: RemoteChangeKind,
+}
+
+struct ActiveWatch {
+    stop_flag: Arc<AtomicBool>,
+}
+
+/// Реестр наблюдателей за удаленными путями, управляемый через tauri::State.
+/// Каждый наблюдатель опрашивает состояние пути (mtime/размер через `stat`/`Get-Item`)
+/// на каждом выбранном хосте через существующий пул соединений и эмитит `remote-change`
+/// при появлении, изменении или удалении пути. Полезно, например, для слежения за
+/// лог-файлом, который создает пакетная команда.
+#[derive(Default)]
+pub struct RemoteWatchManager {
+    watches: Mutex<HashMap<String, ActiveWatch>>,
+}
+
+impl RemoteWatchManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Запускает наблюдение за `path` на каждом из `hosts`, по одному фоновому потоку на хост.
+    /// Возвращает `watch_id`, используемый для последующей остановки через `unwatch`.
+    pub fn watch(
+        &self,
+        path: String,
+        hosts: Vec<SshConfig>,
+        poll_interval: std::time::Duration,
+        pool: Arc<SshConnectionPool>,
+        window: Window,
+    ) -> String {
+        let watch_id = Uuid::new_v4().to_string();
+        let stop_flag = Arc::new(AtomicBool::new(false));
+
+        self.watches
+            .lock()
+            .insert(watch_id.clone(), ActiveWatch { stop_flag: stop_flag.clone() });
+
+        for config in hosts {
+            let host_label = config.host.clone();
+            let path = path.clone();
+            let pool = pool.clone();
+            let window = window.clone();
+            let stop_flag = stop_flag.clone();
+            let watch_id = watch_id.clone();
+            std::thread::spawn(move || {
+                poll_host(watch_id, host_label, path, config, pool, stop_flag, poll_interval, window);
+            });
+        }
+
+        watch_id
+    }
+
+    /// Останавливает все фоновые потоки, связанные с `watch_id`
+    pub fn unwatch(&self, watch_id: &str) -> bool {
+        if let Some(watch) = self.watches.lock().remove(watch_id) {
+            watch.stop_flag.store(true, Ordering::SeqCst);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Останавливает все активные наблюдения (например, при глобальной отмене выполнения)
+    pub fn stop_all(&self) {
+        let mut watches = self.watches.lock();
+        for (_, watch) in watches.drain() {
+            watch.stop_flag.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+fn poll_host(
+    watch_id: String,
+    host: String,
+    path: String,
+    config: SshConfig,
+    pool: Arc<SshConnectionPool>,
+    stop_flag: Arc<AtomicBool>,
+    poll_interval: std::time::Duration,
+    window: Window,
+) {
+    let mut last_state: Option<(String, String)> = None;
+    let mut first_poll_done = false;
+
+    while !stop_flag.load(Ordering::SeqCst) {
+        let connection = match pool.get_or_create(config.clone()) {
+            Ok(c) => c,
+            Err(e) => {
+                log::warn!("[Watch] Не удалось подключиться к {} для наблюдения за {}: {}", host, path, e);
+                if interruptible_sleep(&stop_flag, poll_interval) {
+                    break;
+                }
+                continue;
+            }
+        };
+
+        let stat_cmd = stat_command_for_family(connection.family(), &path);
+        match connection.execute_command(&stat_cmd) {
+            Ok(result) => {
+                let current_state = parse_stat_output(&result.stdout);
+                // `had_baseline` отличает самый первый опрос (где состояние лишь фиксируется)
+                // от повторного появления пути после удаления (где это уже Created)
+                let had_baseline = last_state.is_some() || first_poll_done;
+                match (&last_state, &current_state) {
+                    (None, Some(_)) if had_baseline => {
+                        emit_change(&window, &watch_id, &host, &path, RemoteChangeKind::Created);
+                    }
+                    (Some(prev), Some(state)) if prev != state => {
+                        emit_change(&window, &watch_id, &host, &path, RemoteChangeKind::Modified);
+                    }
+                    (Some(_), None) => {
+                        emit_change(&window, &watch_id, &host, &path, RemoteChangeKind::Removed);
+                    }
+                    _ => {}
+                }
+                last_state = current_state;
+                first_poll_done = true;
+            }
+            Err(e) => {
+                log::warn!("[Watch] Ошибка проверки {} на {}: {}", path, host, e);
+            }
+        }
+
+        if interruptible_sleep(&stop_flag, poll_interval) {
+            break;
+        }
+    }
+}
+
+fn stat_command_for_family(family: SshFamily, path: &str) -> String {
+    let escaped = path.replace('\'', "'\\''");
+    match family {
+        SshFamily::Unix => format!("stat -c '%Y|%s' '{}' 2>/dev/null", escaped),
+        SshFamily::Windows => format!(
+            "powershell -NoProfile -Command \"(Get-Item -LiteralPath '{}' -ErrorAction SilentlyContinue | ForEach-Object {{ $_.LastWriteTimeUtc.Ticks.ToString() + '|' + $_.Length }})\"",
+            path.replace('\'', "''")
+        ),
+    }
+}
+
+fn parse_stat_output(stdout: &str) -> Option<(String, String)> {
+    let line = stdout.trim();
+    if line.is_empty() {
+        return None;
+    }
+    let mut parts = line.splitn(2, '|');
+    let mtime = parts.next()?.to_string();
+    let size = parts.next()?.to_string();
+    Some((mtime, size))
+}
+
+fn emit_change(window: &Window, watch_id: &str, host: &str, path: &str, kind: RemoteChangeKind) {
+    let _ = window.emit(
+        "remote-change",
+        RemoteChangeEvent {
+            watch_id: watch_id.to_string(),
+            host: host.to_string(),
+            path: path.to_string(),
+            kind,
+        },
+    );
+}
+
+fn interruptible_sleep(stop_flag: &Arc<AtomicBool>, duration: std::time::Duration) -> bool {
+    let step = std::time::Duration::from_millis(100);
+    let mut remaining = duration;
+    while remaining > std::time::Duration::ZERO {
+        if stop_flag.load(Ordering::SeqCst) {
+            return true;
+        }
+        let sleep_time = remaining.min(step);
+        std::thread::sleep(sleep_time);
+        remaining = remaining.saturating_sub(sleep_time);
+    }
+    false
+}